@@ -0,0 +1,544 @@
+use std::collections::BTreeMap;
+use std::mem;
+
+use crate::id::{IdPath, Level, NodePath};
+use crate::state::{Dependency, Effect};
+use crate::subscription::{Subscribers, SubscriptionRegistry};
+
+#[derive(Debug)]
+struct Item<T> {
+    value: T,
+    subscribers: Subscribers,
+}
+
+impl<T> Item<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            subscribers: Subscribers::default(),
+        }
+    }
+}
+
+impl<T: Clone> Clone for Item<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.value.clone())
+    }
+}
+
+#[derive(Debug)]
+pub struct AtomVec<T> {
+    items: Vec<Item<T>>,
+    version: usize,
+    subscribers: Subscribers,
+}
+
+impl<T> AtomVec<T> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            items: Vec::new(),
+            version: 0,
+            subscribers: Subscribers::default(),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index).map(|item| &item.value)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.items.iter().map(|item| &item.value)
+    }
+
+    #[inline]
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    #[inline]
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().len()
+    }
+
+    #[inline]
+    pub fn item_subscriber_count(&self, index: usize) -> usize {
+        self.items
+            .get(index)
+            .map_or(0, |item| item.subscribers.lock().len())
+    }
+
+    pub fn push(&mut self, value: T) -> Effect {
+        self.items.push(Item::new(value));
+        self.notify_structure(Vec::new(), self.items.len())
+    }
+
+    pub fn insert(&mut self, index: usize, value: T) -> Effect {
+        self.items.insert(index, Item::new(value));
+        self.notify_structure(Vec::new(), index)
+    }
+
+    pub fn remove(&mut self, index: usize) -> Effect {
+        if index < self.items.len() {
+            let item = self.items.remove(index);
+            let subscribers = mem::take(&mut *item.subscribers.lock());
+            self.notify_structure(subscribers, index)
+        } else {
+            Effect::Nop
+        }
+    }
+
+    pub fn retain<F>(&mut self, mut f: F) -> Effect
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut index = 0;
+        let mut first_removed_index = None;
+        let mut subscribers = Vec::new();
+        self.items.retain(|item| {
+            let retained = f(&item.value);
+            if !retained {
+                first_removed_index.get_or_insert(index);
+                subscribers.append(&mut item.subscribers.lock());
+            }
+            index += 1;
+            retained
+        });
+        match first_removed_index {
+            Some(index) => self.notify_structure(subscribers, index),
+            None => Effect::Nop,
+        }
+    }
+
+    pub fn clear(&mut self) -> Effect {
+        if self.items.is_empty() {
+            Effect::Nop
+        } else {
+            let mut subscribers = Vec::new();
+            for item in self.items.drain(..) {
+                subscribers.append(&mut item.subscribers.lock());
+            }
+            self.notify_structure(subscribers, 0)
+        }
+    }
+
+    pub fn set_item(&mut self, index: usize, value: T) -> Effect {
+        self.update_item(index, move |item| *item = value)
+    }
+
+    pub fn update_item<F>(&mut self, index: usize, f: F) -> Effect
+    where
+        F: FnOnce(&mut T),
+    {
+        if let Some(item) = self.items.get_mut(index) {
+            f(&mut item.value);
+            self.version = self.version.wrapping_add(1);
+            Effect::Update(mem::take(&mut *item.subscribers.lock()))
+        } else {
+            Effect::Nop
+        }
+    }
+
+    pub(crate) fn subscribe(
+        &self,
+        id_path: &IdPath,
+        level: Level,
+        subscriptions: &SubscriptionRegistry,
+    ) {
+        subscriptions.subscribe(&self.subscribers, id_path, level);
+    }
+
+    pub(crate) fn subscribe_item(
+        &self,
+        index: usize,
+        id_path: &IdPath,
+        level: Level,
+        subscriptions: &SubscriptionRegistry,
+    ) -> Option<&T> {
+        match self.items.get(index) {
+            Some(item) => {
+                subscriptions.subscribe(&item.subscribers, id_path, level);
+                Some(&item.value)
+            }
+            None => {
+                subscriptions.subscribe(&self.subscribers, id_path, level);
+                None
+            }
+        }
+    }
+
+    // Items at or after `index` have moved, so their subscribers see a different item now.
+    fn notify_structure(&mut self, mut subscribers: Vec<NodePath>, index: usize) -> Effect {
+        self.version = self.version.wrapping_add(1);
+        subscribers.append(&mut self.subscribers.lock());
+        for item in self.items.iter().skip(index) {
+            subscribers.append(&mut item.subscribers.lock());
+        }
+        Effect::Update(subscribers)
+    }
+}
+
+impl<T> Default for AtomVec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> Clone for AtomVec<T> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+            version: self.version,
+            subscribers: Subscribers::default(),
+        }
+    }
+}
+
+impl<T> FromIterator<T> for AtomVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            items: iter.into_iter().map(Item::new).collect(),
+            version: 0,
+            subscribers: Subscribers::default(),
+        }
+    }
+}
+
+impl<T> Dependency for &AtomVec<T> {
+    fn collect_versions(&self, versions: &mut Vec<usize>) {
+        versions.push(self.version);
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for AtomVec<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for AtomVec<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::<T>::deserialize(deserializer).map(|items| items.into_iter().collect())
+    }
+}
+
+#[derive(Debug)]
+pub struct AtomMap<K, V> {
+    entries: BTreeMap<K, Item<V>>,
+    version: usize,
+    subscribers: Subscribers,
+}
+
+impl<K: Ord, V> AtomMap<K, V> {
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            version: 0,
+            subscribers: Subscribers::default(),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries.get(key).map(|entry| &entry.value)
+    }
+
+    #[inline]
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    #[inline]
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator {
+        self.entries.iter().map(|(key, entry)| (key, &entry.value))
+    }
+
+    #[inline]
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.entries.keys()
+    }
+
+    #[inline]
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    #[inline]
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().len()
+    }
+
+    #[inline]
+    pub fn entry_subscriber_count(&self, key: &K) -> usize {
+        self.entries
+            .get(key)
+            .map_or(0, |entry| entry.subscribers.lock().len())
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Effect {
+        match self.entries.get_mut(&key) {
+            Some(entry) => {
+                entry.value = value;
+                self.version = self.version.wrapping_add(1);
+                Effect::Update(mem::take(&mut *entry.subscribers.lock()))
+            }
+            None => {
+                self.entries.insert(key, Item::new(value));
+                self.notify_structure(Vec::new())
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &K) -> Effect {
+        if let Some(entry) = self.entries.remove(key) {
+            let subscribers = mem::take(&mut *entry.subscribers.lock());
+            self.notify_structure(subscribers)
+        } else {
+            Effect::Nop
+        }
+    }
+
+    pub fn retain<F>(&mut self, mut f: F) -> Effect
+    where
+        F: FnMut(&K, &V) -> bool,
+    {
+        let len = self.entries.len();
+        let mut subscribers = Vec::new();
+        self.entries.retain(|key, entry| {
+            let retained = f(key, &entry.value);
+            if !retained {
+                subscribers.append(&mut entry.subscribers.lock());
+            }
+            retained
+        });
+        if self.entries.len() != len {
+            self.notify_structure(subscribers)
+        } else {
+            Effect::Nop
+        }
+    }
+
+    pub fn clear(&mut self) -> Effect {
+        if self.entries.is_empty() {
+            Effect::Nop
+        } else {
+            let mut subscribers = Vec::new();
+            for entry in mem::take(&mut self.entries).into_values() {
+                subscribers.append(&mut entry.subscribers.lock());
+            }
+            self.notify_structure(subscribers)
+        }
+    }
+
+    pub fn update_item<F>(&mut self, key: &K, f: F) -> Effect
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Some(entry) = self.entries.get_mut(key) {
+            f(&mut entry.value);
+            self.version = self.version.wrapping_add(1);
+            Effect::Update(mem::take(&mut *entry.subscribers.lock()))
+        } else {
+            Effect::Nop
+        }
+    }
+
+    pub(crate) fn subscribe(
+        &self,
+        id_path: &IdPath,
+        level: Level,
+        subscriptions: &SubscriptionRegistry,
+    ) {
+        subscriptions.subscribe(&self.subscribers, id_path, level);
+    }
+
+    pub(crate) fn subscribe_item(
+        &self,
+        key: &K,
+        id_path: &IdPath,
+        level: Level,
+        subscriptions: &SubscriptionRegistry,
+    ) -> Option<&V> {
+        match self.entries.get(key) {
+            Some(entry) => {
+                subscriptions.subscribe(&entry.subscribers, id_path, level);
+                Some(&entry.value)
+            }
+            None => {
+                subscriptions.subscribe(&self.subscribers, id_path, level);
+                None
+            }
+        }
+    }
+
+    fn notify_structure(&mut self, mut subscribers: Vec<NodePath>) -> Effect {
+        self.version = self.version.wrapping_add(1);
+        subscribers.append(&mut self.subscribers.lock());
+        Effect::Update(subscribers)
+    }
+}
+
+impl<K: Ord, V> Default for AtomMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Clone, V: Clone> Clone for AtomMap<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            version: self.version,
+            subscribers: Subscribers::default(),
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for AtomMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            entries: iter
+                .into_iter()
+                .map(|(key, value)| (key, Item::new(value)))
+                .collect(),
+            version: 0,
+            subscribers: Subscribers::default(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<K: serde::Serialize + Ord, V: serde::Serialize> serde::Serialize for AtomMap<K, V> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, K, V> serde::Deserialize<'de> for AtomMap<K, V>
+where
+    K: serde::Deserialize<'de> + Ord,
+    V: serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        BTreeMap::<K, V>::deserialize(deserializer).map(|entries| entries.into_iter().collect())
+    }
+}
+
+impl<K, V> Dependency for &AtomMap<K, V> {
+    fn collect_versions(&self, versions: &mut Vec<usize>) {
+        versions.push(self.version);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::id::{Id, NodePath};
+
+    fn paths(effect: Effect) -> Vec<NodePath> {
+        match effect {
+            Effect::Update(paths) => paths,
+            _ => Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_atom_vec() {
+        let subscriptions = SubscriptionRegistry::new();
+        let list_path = [Id::ROOT];
+        let mut atom_vec = ["foo", "bar"].into_iter().collect::<AtomVec<_>>();
+
+        atom_vec.subscribe(&list_path, 1, &subscriptions);
+        assert_eq!(
+            atom_vec.subscribe_item(1, &list_path, 1, &subscriptions),
+            Some(&"bar")
+        );
+
+        assert!(paths(atom_vec.update_item(0, |item| *item = "baz")).is_empty());
+        assert_eq!(paths(atom_vec.set_item(1, "qux")).len(), 1);
+        assert_eq!(atom_vec.item_subscriber_count(1), 0);
+        assert_eq!(atom_vec.subscriber_count(), 1);
+
+        assert_eq!(paths(atom_vec.push("quux")).len(), 1);
+        assert_eq!(
+            atom_vec.iter().copied().collect::<Vec<_>>(),
+            ["baz", "qux", "quux"]
+        );
+        assert!(matches!(atom_vec.retain(|_| true), Effect::Nop));
+    }
+
+    #[test]
+    fn test_atom_vec_shift() {
+        let subscriptions = SubscriptionRegistry::new();
+        let path = [Id::ROOT];
+        let mut atom_vec = ["a", "b", "c", "d"].into_iter().collect::<AtomVec<_>>();
+        let subscribe_all = |atom_vec: &AtomVec<_>| {
+            for index in 0..atom_vec.len() {
+                atom_vec.subscribe_item(index, &path, 1, &subscriptions);
+            }
+        };
+
+        subscribe_all(&atom_vec);
+        assert_eq!(paths(atom_vec.insert(2, "x")).len(), 2);
+        assert_eq!(atom_vec.item_subscriber_count(1), 1);
+        assert_eq!(paths(atom_vec.remove(0)).len(), 2);
+
+        subscribe_all(&atom_vec);
+        assert_eq!(paths(atom_vec.retain(|item| *item != "x")).len(), 3);
+        assert_eq!(atom_vec.item_subscriber_count(0), 1);
+        assert_eq!(paths(atom_vec.clear()).len(), 1);
+    }
+
+    #[test]
+    fn test_atom_map() {
+        let subscriptions = SubscriptionRegistry::new();
+        let path = [Id::ROOT];
+        let mut atom_map = AtomMap::new();
+        let _ = atom_map.insert(1, "foo");
+
+        assert_eq!(atom_map.subscribe_item(&2, &path, 1, &subscriptions), None);
+        assert_eq!(atom_map.subscriber_count(), 1);
+        assert_eq!(paths(atom_map.insert(2, "bar")).len(), 1);
+
+        assert_eq!(
+            atom_map.subscribe_item(&2, &path, 1, &subscriptions),
+            Some(&"bar")
+        );
+        assert!(paths(atom_map.update_item(&1, |value| *value = "baz")).is_empty());
+        assert_eq!(paths(atom_map.insert(2, "qux")).len(), 1);
+        assert!(paths(atom_map.remove(&1)).is_empty());
+        assert_eq!(atom_map.keys().copied().collect::<Vec<_>>(), [2]);
+
+        assert_eq!(
+            atom_map.subscribe_item(&2, &path, 1, &subscriptions),
+            Some(&"qux")
+        );
+        assert_eq!(paths(atom_map.remove(&2)).len(), 1);
+    }
+}
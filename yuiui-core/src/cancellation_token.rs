@@ -58,6 +58,8 @@ impl CancellationToken {
 
     pub fn cancel(&self) {
         if let Some(token) = self.take() {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("cancel command");
             token.cancel();
         }
     }
@@ -91,15 +93,15 @@ impl fmt::Debug for CancellationToken {
     }
 }
 
-unsafe impl Send for CancellationToken {}
-
-unsafe impl Sync for CancellationToken {}
-
 struct Inner {
     state: AtomicUsize,
     token: UnsafeCell<Option<RawToken>>,
 }
 
+unsafe impl Send for Inner {}
+
+unsafe impl Sync for Inner {}
+
 impl Drop for Inner {
     fn drop(&mut self) {
         if let Some(token) = unsafe { (*self.token.get()).take() } {
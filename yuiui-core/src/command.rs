@@ -1,10 +1,12 @@
 use futures::future::{BoxFuture, FutureExt as _};
 use futures::stream::{BoxStream, Stream, StreamExt as _};
+use std::any::Any;
 use std::fmt;
 use std::future::Future;
 use std::time::Duration;
 
 use crate::cancellation_token::CancellationToken;
+use crate::state::Effect;
 
 pub enum Command<T> {
     Future(BoxFuture<'static, T>),
@@ -14,6 +16,15 @@ pub enum Command<T> {
 }
 
 impl<T> Command<T> {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Future(_) => "future",
+            Self::Stream(_) => "stream",
+            Self::Timeout(..) => "timeout",
+            Self::Interval(..) => "interval",
+        }
+    }
+
     pub fn from_future<Future>(future: Future) -> Self
     where
         Future: self::Future<Output = T> + Send + 'static,
@@ -41,6 +52,7 @@ impl<T> Command<T> {
     {
         Self::Interval(period, Box::new(f))
     }
+
     pub fn map<F, U>(self, mut f: F) -> Command<U>
     where
         F: FnMut(T) -> U + Send + 'static,
@@ -79,6 +91,173 @@ where
     }
 }
 
+pub struct Commands<T> {
+    pub(crate) entries: Vec<(Command<T>, Option<CancellationToken>)>,
+}
+
+impl<T> Commands<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, command: Command<T>) {
+        self.entries.push((command, None));
+    }
+
+    pub fn push_cancellable(&mut self, command: Command<T>, cancellation_token: CancellationToken) {
+        self.entries.push((command, Some(cancellation_token)));
+    }
+
+    pub fn append(&mut self, other: &mut Self) {
+        self.entries.append(&mut other.entries);
+    }
+
+    pub fn map<F, U>(self, f: F) -> Commands<U>
+    where
+        F: FnMut(T) -> U + Clone + Send + 'static,
+        T: 'static,
+    {
+        let entries = self
+            .entries
+            .into_iter()
+            .map(|(command, cancellation_token)| (command.map(f.clone()), cancellation_token))
+            .collect();
+        Commands { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<T> Default for Commands<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Commands<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Commands")
+            .field("len", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
+
+pub struct Subscriptions<T> {
+    pub(crate) entries: Vec<(SubscriptionKey, SubscriptionFactory<T>)>,
+}
+
+type SubscriptionFactory<T> = Box<dyn FnOnce() -> Command<T>>;
+
+impl<T> Subscriptions<T> {
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn push<K, F>(&mut self, key: K, f: F)
+    where
+        K: PartialEq + 'static,
+        F: FnOnce() -> Command<T> + 'static,
+    {
+        self.entries.push((SubscriptionKey::new(key), Box::new(f)));
+    }
+
+    pub fn map<F, U>(self, f: F) -> Subscriptions<U>
+    where
+        F: FnMut(T) -> U + Clone + Send + 'static,
+        T: 'static,
+    {
+        let entries = self
+            .entries
+            .into_iter()
+            .map(|(key, factory)| {
+                let f = f.clone();
+                let factory: SubscriptionFactory<U> = Box::new(move || factory().map(f));
+                (key, factory)
+            })
+            .collect();
+        Subscriptions { entries }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<T> Default for Subscriptions<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> fmt::Debug for Subscriptions<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Subscriptions")
+            .field("len", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
+
+pub(crate) struct SubscriptionKey {
+    key: Box<dyn Any>,
+    eq: fn(&dyn Any, &dyn Any) -> bool,
+}
+
+impl SubscriptionKey {
+    fn new<K: PartialEq + 'static>(key: K) -> Self {
+        Self {
+            key: Box::new(key),
+            eq: |lhs, rhs| match (lhs.downcast_ref::<K>(), rhs.downcast_ref::<K>()) {
+                (Some(lhs), Some(rhs)) => lhs == rhs,
+                _ => false,
+            },
+        }
+    }
+}
+
+impl PartialEq for SubscriptionKey {
+    fn eq(&self, other: &Self) -> bool {
+        (self.eq)(&*self.key, &*other.key)
+    }
+}
+
+impl fmt::Debug for SubscriptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SubscriptionKey").finish_non_exhaustive()
+    }
+}
+
 pub trait CommandRuntime<T> {
     fn spawn_command(&self, command: Command<T>, cancellation_token: Option<CancellationToken>);
+
+    fn spawn_effect(&self, command: Command<Effect>, cancellation_token: Option<CancellationToken>);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscription_key() {
+        assert_eq!(SubscriptionKey::new(1u32), SubscriptionKey::new(1u32));
+        assert_ne!(SubscriptionKey::new(1u32), SubscriptionKey::new(2u32));
+        assert_ne!(SubscriptionKey::new(1u32), SubscriptionKey::new(1u64));
+        assert_eq!(
+            SubscriptionKey::new(("clock", 1)),
+            SubscriptionKey::new(("clock", 1))
+        );
+    }
 }
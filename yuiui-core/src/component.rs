@@ -1,5 +1,5 @@
-use std::fmt;
 use std::marker::PhantomData;
+use std::{any, fmt};
 
 use crate::context::RenderContext;
 use crate::element::{ComponentElement, Element, MemoizeElement};
@@ -9,6 +9,11 @@ pub trait Component<S, M, E>: Sized {
 
     fn render(&self, context: &mut RenderContext<S>) -> Self::Element;
 
+    #[inline]
+    fn name(&self) -> &'static str {
+        any::type_name::<Self>()
+    }
+
     #[inline]
     fn el(self) -> ComponentElement<Self> {
         ComponentElement::new(self)
@@ -51,6 +56,11 @@ where
     fn render(&self, context: &mut RenderContext<S>) -> Self::Element {
         (self.render_fn)(&self.props, context)
     }
+
+    #[inline]
+    fn name(&self) -> &'static str {
+        any::type_name::<RenderFn>()
+    }
 }
 
 impl<RenderFn, Props, Element, S, M, E> AsRef<Props>
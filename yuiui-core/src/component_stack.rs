@@ -1,11 +1,12 @@
 use std::marker::PhantomData;
 
 use crate::component::Component;
-use crate::context::RenderContext;
+use crate::context::{CommitContext, RenderContext};
+use crate::effect;
 use crate::element::Element;
 use crate::id::Level;
 use crate::view::View;
-use crate::view_node::ViewNodeMut;
+use crate::view_node::{CommitMode, ViewNodeMut};
 
 pub trait ComponentStack<S, M, E> {
     const LEVEL: Level;
@@ -17,6 +18,14 @@ pub trait ComponentStack<S, M, E> {
         level: Level,
         context: &mut RenderContext<S>,
     ) -> bool;
+
+    fn commit<'a>(
+        node: &mut ViewNodeMut<'a, Self::View, Self, S, M, E>,
+        mode: CommitMode,
+        context: &mut CommitContext<S, M, E>,
+    );
+
+    fn component_names(&self, names: &mut Vec<&'static str>);
 }
 
 impl<C, CS, S, M, E> ComponentStack<S, M, E> for (C, CS)
@@ -24,6 +33,10 @@ where
     C: Component<S, M, E>,
     C::Element: Element<S, M, E, Components = CS>,
     CS: ComponentStack<S, M, E, View = <C::Element as Element<S, M, E>>::View>,
+    // Effect slots are looked up in the type-erased local slots by downcasting.
+    S: 'static,
+    M: 'static,
+    E: 'static,
 {
     const LEVEL: Level = 1 + CS::LEVEL;
 
@@ -42,16 +55,53 @@ where
             view_state: node.view_state,
             children: node.children,
             components: tail_components,
+            local_states: node.local_states,
             dirty: node.dirty,
         };
         if level >= CS::LEVEL {
-            context.level = Self::LEVEL;
-            let element = head_component.render(context);
-            element.update(&mut node, context)
+            let (element, has_pending_effects) = context.render_component(
+                head_component,
+                Self::LEVEL,
+                &mut node.local_states[Self::LEVEL as usize - 1],
+            );
+            if has_pending_effects {
+                *node.dirty = true;
+            }
+            element.update(&mut node, context) || has_pending_effects
         } else {
             CS::force_update(&mut node, level, context)
         }
     }
+
+    fn commit<'a>(
+        node: &mut ViewNodeMut<'a, Self::View, Self, S, M, E>,
+        mode: CommitMode,
+        context: &mut CommitContext<S, M, E>,
+    ) {
+        let (_, tail_components) = node.components;
+        let mut node = ViewNodeMut {
+            id: node.id,
+            view: node.view,
+            pending_view: node.pending_view,
+            view_state: node.view_state,
+            children: node.children,
+            components: tail_components,
+            local_states: node.local_states,
+            dirty: node.dirty,
+        };
+        if mode == CommitMode::Unmount {
+            effect::commit_effects(&node.local_states[Self::LEVEL as usize - 1], mode, context);
+            CS::commit(&mut node, mode, context);
+        } else {
+            CS::commit(&mut node, mode, context);
+            effect::commit_effects(&node.local_states[Self::LEVEL as usize - 1], mode, context);
+        }
+    }
+
+    fn component_names(&self, names: &mut Vec<&'static str>) {
+        names.push(self.0.name());
+        self.1.component_names(names);
+    }
 }
 
 #[derive(Debug)]
@@ -82,4 +132,13 @@ where
     ) -> bool {
         false
     }
+
+    fn commit<'a>(
+        _node: &mut ViewNodeMut<'a, V, Self, S, M, E>,
+        _mode: CommitMode,
+        _context: &mut CommitContext<S, M, E>,
+    ) {
+    }
+
+    fn component_names(&self, _names: &mut Vec<&'static str>) {}
 }
@@ -1,15 +1,34 @@
+use std::any;
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+use crate::atom_collection::{AtomMap, AtomVec};
 use crate::cancellation_token::CancellationToken;
-use crate::command::Command;
+use crate::command::{Command, Commands};
+use crate::component::Component;
+use crate::deadline::Deadline;
+use crate::effect::{EffectCleanup, EffectSlot};
 use crate::element::Element;
-use crate::id::{IdPath, IdStack, Level};
-use crate::state::Atom;
+use crate::event::Lifecycle;
+use crate::id::{IdPath, IdStack, Level, NodePath};
+use crate::local_state::{LocalReducer, LocalSlotCursor, LocalSlots, LocalState};
+use crate::profiler::Profiler;
+use crate::provider::ProviderRegistry;
+use crate::state::{Atom, Effect, Selector};
+use crate::subscription::SubscriptionRegistry;
 use crate::view_node::ViewNode;
 
-#[derive(Debug)]
 pub struct RenderContext<'context, S> {
     pub(crate) id_stack: &'context mut IdStack,
     pub(crate) state: &'context S,
     pub(crate) level: Level,
+    pub(crate) local_slots: Option<LocalSlotCursor>,
+    pub(crate) pending_updates: &'context mut Vec<NodePath>,
+    pub(crate) providers: &'context mut ProviderRegistry,
+    pub(crate) subscriptions: &'context SubscriptionRegistry,
+    pub(crate) profiler: Option<&'context Profiler>,
+    pub(crate) deadline: Option<&'context dyn Deadline>,
 }
 
 impl<'context, S> RenderContext<'context, S> {
@@ -29,10 +48,153 @@ impl<'context, S> RenderContext<'context, S> {
         F: FnOnce(&S) -> &Atom<T>,
     {
         let atom = f(self.state);
-        atom.subscribe(self.id_stack.id_path(), self.level);
+        atom.subscribe(self.id_stack.id_path(), self.level, self.subscriptions);
         atom.get()
     }
 
+    #[inline]
+    pub fn use_selector<F, T>(&self, f: F) -> &T
+    where
+        F: FnOnce(&S) -> &Selector<T>,
+    {
+        let selector = f(self.state);
+        selector.subscribe(self.id_stack.id_path(), self.level, self.subscriptions);
+        selector.get()
+    }
+
+    #[inline]
+    pub fn use_atom_vec<F, T>(&self, f: F) -> &AtomVec<T>
+    where
+        F: FnOnce(&S) -> &AtomVec<T>,
+    {
+        let atom_vec = f(self.state);
+        atom_vec.subscribe(self.id_stack.id_path(), self.level, self.subscriptions);
+        atom_vec
+    }
+
+    #[inline]
+    pub fn use_atom_item<F, T>(&self, f: F, index: usize) -> Option<&T>
+    where
+        F: FnOnce(&S) -> &AtomVec<T>,
+    {
+        f(self.state).subscribe_item(
+            index,
+            self.id_stack.id_path(),
+            self.level,
+            self.subscriptions,
+        )
+    }
+
+    #[inline]
+    pub fn use_atom_map<F, K, V>(&self, f: F) -> &AtomMap<K, V>
+    where
+        F: FnOnce(&S) -> &AtomMap<K, V>,
+        K: Ord,
+    {
+        let atom_map = f(self.state);
+        atom_map.subscribe(self.id_stack.id_path(), self.level, self.subscriptions);
+        atom_map
+    }
+
+    #[inline]
+    pub fn use_atom_entry<'a, F, K, V>(&'a self, f: F, key: &K) -> Option<&'a V>
+    where
+        F: FnOnce(&S) -> &AtomMap<K, V>,
+        K: Ord + 'a,
+    {
+        f(self.state).subscribe_item(key, self.id_stack.id_path(), self.level, self.subscriptions)
+    }
+
+    #[inline]
+    pub fn use_context<T>(&self) -> Option<Rc<T>>
+    where
+        T: 'static,
+    {
+        self.providers.lookup(self.id_stack.id_path(), self.level)
+    }
+
+    pub fn use_state<F, T>(&mut self, f: F) -> LocalState<T>
+    where
+        F: FnOnce() -> T,
+        T: 'static,
+    {
+        let node_path = NodePath {
+            id_path: self.id_stack.id_path().to_vec(),
+            level: self.level,
+        };
+        let cell = self.local_slots().next(f);
+        LocalState::new(cell, node_path)
+    }
+
+    pub fn use_reducer<F, T, A>(
+        &mut self,
+        f: F,
+        reducer: fn(&mut T, A) -> bool,
+    ) -> LocalReducer<T, A>
+    where
+        F: FnOnce() -> T,
+        T: 'static,
+    {
+        let state = self.use_state(f);
+        LocalReducer::new(state, reducer)
+    }
+
+    pub fn use_effect<D, F, T, M, E>(&mut self, deps: D, f: F)
+    where
+        D: PartialEq + 'static,
+        F: FnOnce(&mut CommitContext<S, M, E>) -> T + 'static,
+        T: Into<EffectCleanup<S, M, E>>,
+        S: 'static,
+        M: 'static,
+        E: 'static,
+    {
+        let local_slots = self.local_slots();
+        let slot = local_slots.next(EffectSlot::<S, M, E>::new);
+        if slot.borrow_mut().schedule(deps, f) {
+            local_slots.schedule_effect();
+        }
+    }
+
+    pub(crate) fn render_component<C, M, E>(
+        &mut self,
+        component: &C,
+        level: Level,
+        local_slots: &mut LocalSlots,
+    ) -> (C::Element, bool)
+    where
+        C: Component<S, M, E>,
+    {
+        let _span = self
+            .profiler
+            .map(|profiler| profiler.enter_component(component.name()));
+        #[cfg(feature = "tracing")]
+        let _tracing_span = tracing::trace_span!(
+            "render",
+            component = component.name(),
+            id_path = ?self.id_path()
+        )
+        .entered();
+        // A rendered component has subscribed again, so it need not be rendered once more when
+        // it is mounted.
+        self.subscriptions.stage(self.id_stack.id());
+        self.level = level;
+        self.local_slots = Some(LocalSlotCursor::new(mem::take(local_slots)));
+        let element = component.render(self);
+        let (new_local_slots, has_pending_effects) = self.local_slots.take().unwrap().into_slots();
+        *local_slots = new_local_slots;
+        (element, has_pending_effects)
+    }
+
+    pub(crate) fn did_timeout(&self) -> bool {
+        self.deadline.is_some_and(|deadline| deadline.did_timeout())
+    }
+
+    fn local_slots(&mut self) -> &mut LocalSlotCursor {
+        self.local_slots
+            .as_mut()
+            .expect("Hooks can only be used while rendering a component")
+    }
+
     pub(crate) fn render_node<Element, M, E>(
         &mut self,
         element: Element,
@@ -41,6 +203,7 @@ impl<'context, S> RenderContext<'context, S> {
         Element: self::Element<S, M, E>,
     {
         let id = self.id_stack.next();
+        self.subscriptions.stage(id);
         self.id_stack.push(id);
         let node = element.render(self);
         self.id_stack.pop();
@@ -58,17 +221,42 @@ impl<'context, S> RenderContext<'context, S> {
         self.id_stack.push(node.id);
         let has_changed = element.update(&mut node.into(), self);
         self.id_stack.pop();
+        if let Some(profiler) = self.profiler {
+            profiler.record_visit(has_changed);
+        }
         has_changed
     }
 }
 
+impl<'context, S: fmt::Debug> fmt::Debug for RenderContext<'context, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RenderContext")
+            .field("id_stack", &self.id_stack)
+            .field("state", &self.state)
+            .field("level", &self.level)
+            .field("local_slots", &self.local_slots)
+            .field("pending_updates", &self.pending_updates)
+            .field("providers", &self.providers)
+            .field("subscriptions", &self.subscriptions)
+            .field("profiler", &self.profiler)
+            .field("deadline", &self.deadline.is_some())
+            .finish()
+    }
+}
+
 #[derive(Debug)]
 pub struct CommitContext<'context, S, M, E> {
     pub(crate) id_stack: &'context mut IdStack,
     pub(crate) state: &'context S,
     pub(crate) messages: &'context mut Vec<M>,
-    pub(crate) commands: &'context mut Vec<(Command<M>, Option<CancellationToken>)>,
+    pub(crate) commands: &'context mut Commands<M>,
+    pub(crate) effect_commands: &'context mut Commands<Effect>,
+    pub(crate) pending_updates: &'context mut Vec<NodePath>,
+    pub(crate) providers: &'context mut ProviderRegistry,
+    pub(crate) subscriptions: &'context SubscriptionRegistry,
+    pub(crate) profiler: Option<&'context Profiler>,
     pub(crate) entry_point: &'context E,
+    pub(crate) in_portal: bool,
 }
 
 impl<'context, S, M, E> CommitContext<'context, S, M, E> {
@@ -87,6 +275,11 @@ impl<'context, S, M, E> CommitContext<'context, S, M, E> {
         self.entry_point
     }
 
+    #[inline]
+    pub fn is_in_portal(&self) -> bool {
+        self.in_portal
+    }
+
     #[inline]
     pub fn dispatch(&mut self, message: M) {
         self.messages.push(message);
@@ -94,7 +287,38 @@ impl<'context, S, M, E> CommitContext<'context, S, M, E> {
 
     #[inline]
     pub fn spawn(&mut self, command: Command<M>, cancellation_token: Option<CancellationToken>) {
-        self.commands.push((command, cancellation_token));
+        self.commands.entries.push((command, cancellation_token));
+    }
+
+    #[inline]
+    pub fn spawn_effect(
+        &mut self,
+        command: Command<Effect>,
+        cancellation_token: Option<CancellationToken>,
+    ) {
+        self.effect_commands
+            .entries
+            .push((command, cancellation_token));
+    }
+
+    pub(crate) fn record_lifecycle<V>(&self, lifecycle: &Lifecycle<V>) {
+        if let Some(profiler) = self.profiler {
+            profiler.record_lifecycle(any::type_name::<V>(), lifecycle);
+        }
+    }
+
+    pub(crate) fn render_context(&mut self) -> RenderContext<'_, S> {
+        RenderContext {
+            id_stack: self.id_stack,
+            state: self.state,
+            level: 0,
+            local_slots: None,
+            pending_updates: self.pending_updates,
+            providers: self.providers,
+            subscriptions: self.subscriptions,
+            profiler: self.profiler,
+            deadline: None,
+        }
     }
 
     pub(crate) fn enter_sub_context<F, FS, FM, T, SS, SM>(
@@ -110,20 +334,24 @@ impl<'context, S, M, E> CommitContext<'context, S, M, E> {
         SM: 'static,
     {
         let mut messages = Vec::new();
-        let mut commands = Vec::new();
+        let mut commands = Commands::new();
         let inner_context = CommitContext {
             id_stack: self.id_stack,
-            state: select_state(&self.state),
+            state: select_state(self.state),
             messages: &mut messages,
             commands: &mut commands,
+            effect_commands: self.effect_commands,
+            pending_updates: self.pending_updates,
+            providers: self.providers,
+            subscriptions: self.subscriptions,
+            profiler: self.profiler,
             entry_point: self.entry_point,
+            in_portal: self.in_portal,
         };
         let result = f(inner_context);
         self.messages.extend(messages.into_iter().map(lift_message));
         self.commands
-            .extend(commands.into_iter().map(|(command, cancellation_token)| {
-                (command.map(lift_message.clone()), cancellation_token)
-            }));
+            .append(&mut commands.map(lift_message.clone()));
         result
     }
 }
@@ -0,0 +1,228 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+pub trait Deadline {
+    fn did_timeout(&self) -> bool;
+
+    fn on_suspend(&self, _pending_work: &PendingWork) {}
+}
+
+impl<T: Deadline + ?Sized> Deadline for &T {
+    fn did_timeout(&self) -> bool {
+        (**self).did_timeout()
+    }
+
+    fn on_suspend(&self, pending_work: &PendingWork) {
+        (**self).on_suspend(pending_work)
+    }
+}
+
+impl Deadline for Instant {
+    fn did_timeout(&self) -> bool {
+        self.saturating_duration_since(Instant::now()) <= Duration::from_millis(1)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PendingWork {
+    pub messages: usize,
+    pub events: usize,
+    pub nodes_to_update: usize,
+    pub nodes_to_commit: usize,
+}
+
+impl PendingWork {
+    pub fn is_empty(&self) -> bool {
+        self.messages == 0
+            && self.events == 0
+            && self.nodes_to_update == 0
+            && self.nodes_to_commit == 0
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Forever;
+
+impl Deadline for Forever {
+    fn did_timeout(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Budget {
+    deadline: Instant,
+}
+
+impl Budget {
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            deadline: Instant::now() + budget,
+        }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+}
+
+impl Deadline for Budget {
+    fn did_timeout(&self) -> bool {
+        self.deadline.did_timeout()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FrameDeadline {
+    frame_time: Instant,
+    frame_interval: Duration,
+    headroom: Duration,
+}
+
+impl FrameDeadline {
+    pub fn new(frame_time: Instant, frame_interval: Duration) -> Self {
+        Self {
+            frame_time,
+            frame_interval,
+            headroom: Duration::from_millis(2),
+        }
+    }
+
+    pub fn with_headroom(mut self, headroom: Duration) -> Self {
+        self.headroom = headroom;
+        self
+    }
+}
+
+impl Deadline for FrameDeadline {
+    fn did_timeout(&self) -> bool {
+        Instant::now() + self.headroom >= self.frame_time + self.frame_interval
+    }
+}
+
+#[derive(Debug)]
+pub struct InputPending<D, F> {
+    deadline: D,
+    is_input_pending: F,
+}
+
+impl<D, F> InputPending<D, F>
+where
+    D: Deadline,
+    F: Fn() -> bool,
+{
+    pub fn new(deadline: D, is_input_pending: F) -> Self {
+        Self {
+            deadline,
+            is_input_pending,
+        }
+    }
+}
+
+impl<D, F> Deadline for InputPending<D, F>
+where
+    D: Deadline,
+    F: Fn() -> bool,
+{
+    fn did_timeout(&self) -> bool {
+        (self.is_input_pending)() || self.deadline.did_timeout()
+    }
+
+    fn on_suspend(&self, pending_work: &PendingWork) {
+        self.deadline.on_suspend(pending_work)
+    }
+}
+
+#[derive(Debug)]
+pub struct StepBudget {
+    remaining_steps: Cell<usize>,
+}
+
+impl StepBudget {
+    pub fn new(steps: usize) -> Self {
+        Self {
+            remaining_steps: Cell::new(steps),
+        }
+    }
+
+    pub fn remaining_steps(&self) -> usize {
+        self.remaining_steps.get()
+    }
+}
+
+impl Deadline for StepBudget {
+    fn did_timeout(&self) -> bool {
+        match self.remaining_steps.get() {
+            0 => true,
+            remaining_steps => {
+                self.remaining_steps.set(remaining_steps - 1);
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_deadline() {
+        let deadline = FrameDeadline::new(Instant::now(), Duration::from_secs(3600));
+        assert!(!deadline.did_timeout());
+
+        let deadline = FrameDeadline::new(Instant::now(), Duration::from_millis(1));
+        assert!(deadline.did_timeout());
+
+        let deadline = FrameDeadline::new(Instant::now(), Duration::from_millis(10))
+            .with_headroom(Duration::from_millis(20));
+        assert!(deadline.did_timeout());
+    }
+
+    #[test]
+    fn test_input_pending() {
+        struct TestDeadline {
+            suspended: Cell<usize>,
+        }
+
+        impl Deadline for TestDeadline {
+            fn did_timeout(&self) -> bool {
+                false
+            }
+
+            fn on_suspend(&self, _pending_work: &PendingWork) {
+                self.suspended.set(self.suspended.get() + 1);
+            }
+        }
+
+        let is_input_pending = Cell::new(false);
+        let deadline = InputPending::new(
+            TestDeadline {
+                suspended: Cell::new(0),
+            },
+            || is_input_pending.get(),
+        );
+        assert!(!deadline.did_timeout());
+        is_input_pending.set(true);
+        assert!(deadline.did_timeout());
+
+        deadline.on_suspend(&PendingWork::default());
+        assert_eq!(deadline.deadline.suspended.get(), 1);
+
+        let deadline = InputPending::new(StepBudget::new(0), || false);
+        assert!(deadline.did_timeout());
+    }
+
+    #[test]
+    fn test_step_budget() {
+        let deadline = StepBudget::new(2);
+        assert!(!deadline.did_timeout());
+        assert_eq!(deadline.remaining_steps(), 1);
+        assert!(!deadline.did_timeout());
+        assert!(deadline.did_timeout());
+        assert!(deadline.did_timeout());
+        assert_eq!(deadline.remaining_steps(), 0);
+
+        assert!(StepBudget::new(0).did_timeout());
+    }
+}
@@ -0,0 +1,193 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::{fmt, mem};
+
+use crate::cancellation_token::CancellationToken;
+use crate::context::CommitContext;
+use crate::local_state::LocalSlots;
+use crate::view_node::CommitMode;
+
+type CleanupFn<S, M, E> = Box<dyn FnOnce(&mut CommitContext<S, M, E>)>;
+
+type EffectFn<S, M, E> = Box<dyn FnOnce(&mut CommitContext<S, M, E>) -> EffectCleanup<S, M, E>>;
+
+pub enum EffectCleanup<S, M, E> {
+    None,
+    Cancel(CancellationToken),
+    Callback(CleanupFn<S, M, E>),
+}
+
+impl<S, M, E> EffectCleanup<S, M, E> {
+    pub fn callback<F>(f: F) -> Self
+    where
+        F: FnOnce(&mut CommitContext<S, M, E>) + 'static,
+    {
+        Self::Callback(Box::new(f))
+    }
+
+    fn run(self, context: &mut CommitContext<S, M, E>) {
+        match self {
+            Self::None => {}
+            Self::Cancel(cancellation_token) => cancellation_token.cancel(),
+            Self::Callback(callback) => callback(context),
+        }
+    }
+}
+
+impl<S, M, E> From<()> for EffectCleanup<S, M, E> {
+    fn from(_: ()) -> Self {
+        Self::None
+    }
+}
+
+impl<S, M, E> From<CancellationToken> for EffectCleanup<S, M, E> {
+    fn from(cancellation_token: CancellationToken) -> Self {
+        Self::Cancel(cancellation_token)
+    }
+}
+
+impl<S, M, E> fmt::Debug for EffectCleanup<S, M, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::None => f.write_str("None"),
+            Self::Cancel(cancellation_token) => {
+                f.debug_tuple("Cancel").field(cancellation_token).finish()
+            }
+            Self::Callback(_) => f.write_str("Callback"),
+        }
+    }
+}
+
+pub(crate) struct EffectSlot<S, M, E> {
+    deps: Option<Box<dyn Any>>,
+    pending_effect: Option<EffectFn<S, M, E>>,
+    cleanup: EffectCleanup<S, M, E>,
+}
+
+impl<S, M, E> EffectSlot<S, M, E> {
+    pub(crate) fn new() -> Self {
+        Self {
+            deps: None,
+            pending_effect: None,
+            cleanup: EffectCleanup::None,
+        }
+    }
+
+    pub(crate) fn schedule<D, F, T>(&mut self, deps: D, f: F) -> bool
+    where
+        D: PartialEq + 'static,
+        F: FnOnce(&mut CommitContext<S, M, E>) -> T + 'static,
+        T: Into<EffectCleanup<S, M, E>>,
+    {
+        let has_changed = self
+            .deps
+            .as_ref()
+            .and_then(|old_deps| old_deps.downcast_ref::<D>())
+            != Some(&deps);
+        if has_changed {
+            self.deps = Some(Box::new(deps));
+            self.pending_effect = Some(Box::new(move |context| f(context).into()));
+        }
+        has_changed
+    }
+}
+
+pub(crate) fn commit_effects<S, M, E>(
+    slots: &LocalSlots,
+    mode: CommitMode,
+    context: &mut CommitContext<S, M, E>,
+) where
+    S: 'static,
+    M: 'static,
+    E: 'static,
+{
+    for slot in slots {
+        if let Some(slot) = slot.downcast_ref::<RefCell<EffectSlot<S, M, E>>>() {
+            let mut slot = slot.borrow_mut();
+            match mode {
+                CommitMode::Mount | CommitMode::Update => {
+                    if let Some(effect) = slot.pending_effect.take() {
+                        let cleanup = mem::replace(&mut slot.cleanup, EffectCleanup::None);
+                        cleanup.run(context);
+                        slot.cleanup = effect(context);
+                    }
+                }
+                CommitMode::Unmount => {
+                    let cleanup = mem::replace(&mut slot.cleanup, EffectCleanup::None);
+                    cleanup.run(context);
+                    slot.deps = None;
+                    slot.pending_effect = None;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{FunctionComponent, HigherOrderComponent};
+    use crate::context::RenderContext;
+    use crate::element::{ComponentElement, ViewElement};
+    use crate::render_loop::RenderLoop;
+    use crate::test_utils::{Container, Label, NullRuntime, TestState};
+    use crate::view::View;
+
+    type Message = Option<u32>;
+
+    type LabelElement = ViewElement<Label, TestState<Message>, Message, ()>;
+
+    type RenderFn = fn(&u32, &mut RenderContext<TestState<Message>>) -> LabelElement;
+
+    type ChildElement = ComponentElement<
+        FunctionComponent<RenderFn, u32, LabelElement, TestState<Message>, Message, ()>,
+    >;
+
+    fn child(value: &u32, context: &mut RenderContext<TestState<Message>>) -> LabelElement {
+        let value = *value;
+        context.use_effect(
+            value,
+            move |context: &mut CommitContext<TestState<Message>, Message, ()>| {
+                context.state().push_log(format!("effect {}", value));
+                EffectCleanup::callback(
+                    move |context: &mut CommitContext<TestState<Message>, Message, ()>| {
+                        context.state().push_log(format!("cleanup {}", value));
+                    },
+                )
+            },
+        );
+        Label(format!("child {}", value)).el(())
+    }
+
+    fn app(
+        _props: &(),
+        context: &mut RenderContext<TestState<Message>>,
+    ) -> ViewElement<Container<Option<ChildElement>>, TestState<Message>, Message, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        Container::new().el(value.map(|value| (child as RenderFn).el(value)))
+    }
+
+    #[test]
+    fn test_use_effect() {
+        let mut state = TestState::new(Some(1));
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount child 1", "effect 1"]);
+
+        render_loop.push_message(Some(1));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update child 1"]);
+
+        render_loop.push_message(Some(2));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec!["update child 2", "cleanup 1", "effect 2"]
+        );
+
+        render_loop.push_message(None);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["cleanup 2", "unmount child 2"]);
+    }
+}
@@ -0,0 +1,33 @@
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Either<L, R> {
+    Left(L),
+    Right(R),
+}
+
+impl<L, R> Either<L, R> {
+    #[inline]
+    pub fn is_left(&self) -> bool {
+        matches!(self, Self::Left(_))
+    }
+
+    #[inline]
+    pub fn is_right(&self) -> bool {
+        matches!(self, Self::Right(_))
+    }
+
+    #[inline]
+    pub fn as_ref(&self) -> Either<&L, &R> {
+        match self {
+            Self::Left(value) => Either::Left(value),
+            Self::Right(value) => Either::Right(value),
+        }
+    }
+
+    #[inline]
+    pub fn as_mut(&mut self) -> Either<&mut L, &mut R> {
+        match self {
+            Self::Left(value) => Either::Left(value),
+            Self::Right(value) => Either::Right(value),
+        }
+    }
+}
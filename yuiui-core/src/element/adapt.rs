@@ -43,6 +43,12 @@ where
             id_stack: context.id_stack,
             state: (self.select_state)(context.state),
             level: context.level,
+            local_slots: None,
+            pending_updates: context.pending_updates,
+            providers: context.providers,
+            subscriptions: context.subscriptions,
+            profiler: context.profiler,
+            deadline: context.deadline,
         };
         let inner_node = self.inner.render(&mut inner_context);
         ViewNode {
@@ -54,6 +60,7 @@ where
             view_state: inner_node.view_state,
             children: Adapt::new(inner_node.children, self.select_state, self.lift_message),
             components: Adapt::new(inner_node.components, self.select_state, self.lift_message),
+            local_states: inner_node.local_states,
             dirty: inner_node.dirty,
         }
     }
@@ -67,6 +74,12 @@ where
             id_stack: context.id_stack,
             state: (self.select_state)(context.state),
             level: context.level,
+            local_slots: None,
+            pending_updates: context.pending_updates,
+            providers: context.providers,
+            subscriptions: context.subscriptions,
+            profiler: context.profiler,
+            deadline: context.deadline,
         };
         node.view.select_state = self.select_state;
         node.view.lift_message = self.lift_message;
@@ -173,6 +186,10 @@ where
             self.inner.build(&mut children.inner, &mut context)
         })
     }
+
+    fn inspect(&self) -> Option<String> {
+        self.inner.inspect()
+    }
 }
 
 impl<'event, Inner, S, M, SS, SM> EventTarget<'event> for Adapt<Inner, S, M, SS, SM>
@@ -201,11 +218,35 @@ where
             id_stack: context.id_stack,
             state: (node.components.select_state)(context.state),
             level: context.level,
+            local_slots: None,
+            pending_updates: context.pending_updates,
+            providers: context.providers,
+            subscriptions: context.subscriptions,
+            profiler: context.profiler,
+            deadline: context.deadline,
         };
         with_inner_node(node, |mut inner_node| {
             Inner::force_update(&mut inner_node, level, &mut inner_context)
         })
     }
+
+    fn commit<'a>(
+        node: &mut ViewNodeMut<'a, Self::View, Self, S, M, E>,
+        mode: CommitMode,
+        context: &mut CommitContext<S, M, E>,
+    ) {
+        let select_state = node.components.select_state;
+        let lift_message = node.components.lift_message;
+        context.enter_sub_context(&select_state, &lift_message, |mut inner_context| {
+            with_inner_node(node, |mut inner_node| {
+                Inner::commit(&mut inner_node, mode, &mut inner_context)
+            })
+        })
+    }
+
+    fn component_names(&self, names: &mut Vec<&'static str>) {
+        self.inner.component_names(names);
+    }
 }
 
 impl<Inner, S, M, SS, SM, E> ElementSeq<S, M, E> for Adapt<Inner, S, M, SS, SM>
@@ -221,6 +262,12 @@ where
             id_stack: context.id_stack,
             state: (self.select_state)(context.state),
             level: context.level,
+            local_slots: None,
+            pending_updates: context.pending_updates,
+            providers: context.providers,
+            subscriptions: context.subscriptions,
+            profiler: context.profiler,
+            deadline: context.deadline,
         };
         Adapt::new(
             self.inner.render_children(&mut inner_context),
@@ -234,6 +281,12 @@ where
             id_stack: context.id_stack,
             state: (self.select_state)(context.state),
             level: context.level,
+            local_slots: None,
+            pending_updates: context.pending_updates,
+            providers: context.providers,
+            subscriptions: context.subscriptions,
+            profiler: context.profiler,
+            deadline: context.deadline,
         };
         self.inner
             .update_children(&mut storage.inner, &mut inner_context)
@@ -273,6 +326,12 @@ where
             id_stack: context.id_stack,
             state: (self.select_state)(context.state),
             level: context.level,
+            local_slots: None,
+            pending_updates: context.pending_updates,
+            providers: context.providers,
+            subscriptions: context.subscriptions,
+            profiler: context.profiler,
+            deadline: context.deadline,
         };
         self.inner.for_each(visitor, &mut inner_context)
     }
@@ -287,6 +346,12 @@ where
             id_stack: context.id_stack,
             state: (self.select_state)(context.state),
             level: context.level,
+            local_slots: None,
+            pending_updates: context.pending_updates,
+            providers: context.providers,
+            subscriptions: context.subscriptions,
+            profiler: context.profiler,
+            deadline: context.deadline,
         };
         self.inner.for_id(id, visitor, &mut inner_context)
     }
@@ -326,6 +391,7 @@ where
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn with_inner_node<F, T, V, CS, S, M, SS, SM, E>(
     node: &mut ViewNodeMut<Adapt<V, S, M, SS, SM>, Adapt<CS, S, M, SS, SM>, S, M, E>,
     f: F,
@@ -334,6 +400,8 @@ where
     F: FnOnce(ViewNodeMut<V, CS, SS, SM, E>) -> T,
     V: View<SS, SM, E>,
     CS: ComponentStack<SS, SM, E, View = V>,
+    M: 'static,
+    SM: 'static,
 {
     let mut inner_pending_view = node.pending_view.take().map(|view| view.inner);
     let inner_node = ViewNodeMut {
@@ -343,6 +411,7 @@ where
         view_state: node.view_state,
         children: &mut node.children.inner,
         components: &mut node.components.inner,
+        local_states: node.local_states,
         dirty: node.dirty,
     };
     let select_state = &node.view.select_state;
@@ -0,0 +1,231 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::component_stack::ComponentTermination;
+use crate::context::{CommitContext, RenderContext};
+use crate::event::{EventTarget, Lifecycle};
+use crate::storages::{BoxedElement, Projection};
+use crate::view::View;
+use crate::view_node::{ViewNode, ViewNodeMut};
+
+use super::{Element, ElementSeq};
+
+pub struct AnyElement<S, M, E, W = ()> {
+    inner: BoxedElement<S, M, E, W>,
+}
+
+impl<S, M, E> AnyElement<S, M, E>
+where
+    S: 'static,
+    M: 'static,
+    E: 'static,
+{
+    pub fn new<Element>(element: Element) -> Self
+    where
+        Element: self::Element<S, M, E> + 'static,
+    {
+        Self::with_projection(element, |_| &())
+    }
+}
+
+impl<S, M, E, W> AnyElement<S, M, E, W>
+where
+    S: 'static,
+    M: 'static,
+    E: 'static,
+    W: 'static,
+{
+    pub fn with_projection<Element>(
+        element: Element,
+        project: Projection<<Element::View as View<S, M, E>>::State, W>,
+    ) -> Self
+    where
+        Element: self::Element<S, M, E> + 'static,
+    {
+        Self {
+            inner: BoxedElement::new(element, project),
+        }
+    }
+}
+
+impl<S, M, E, W> Element<S, M, E> for AnyElement<S, M, E, W>
+where
+    S: 'static,
+    M: 'static,
+    E: 'static,
+    W: Clone + 'static,
+{
+    type View = AnyView<W>;
+
+    type Components = ComponentTermination<AnyView<W>>;
+
+    fn render(
+        self,
+        context: &mut RenderContext<S>,
+    ) -> ViewNode<Self::View, Self::Components, S, M, E> {
+        let id = context.id_stack.id();
+        let children = self.inner.render_children(context);
+        ViewNode::new(id, AnyView::new(), children, ComponentTermination::new())
+    }
+
+    fn update(
+        self,
+        node: &mut ViewNodeMut<Self::View, Self::Components, S, M, E>,
+        context: &mut RenderContext<S>,
+    ) -> bool {
+        let has_changed = self.inner.update_children(node.children, context);
+        if has_changed {
+            *node.pending_view = Some(AnyView::new());
+            *node.dirty = true;
+        }
+        has_changed
+    }
+}
+
+impl<S, M, E, W> ElementSeq<S, M, E> for AnyElement<S, M, E, W>
+where
+    S: 'static,
+    M: 'static,
+    E: 'static,
+    W: Clone + 'static,
+{
+    type Storage =
+        ViewNode<<Self as Element<S, M, E>>::View, <Self as Element<S, M, E>>::Components, S, M, E>;
+
+    fn render_children(self, context: &mut RenderContext<S>) -> Self::Storage {
+        context.render_node(self)
+    }
+
+    fn update_children(self, storage: &mut Self::Storage, context: &mut RenderContext<S>) -> bool {
+        context.update_node(self, storage)
+    }
+}
+
+impl<S, M, E, W> fmt::Debug for AnyElement<S, M, E, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("AnyElement").field(&self.inner).finish()
+    }
+}
+
+pub struct AnyView<W> {
+    _phantom: PhantomData<W>,
+}
+
+impl<W> AnyView<W> {
+    fn new() -> Self {
+        Self {
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<S, M, E, W> View<S, M, E> for AnyView<W>
+where
+    S: 'static,
+    M: 'static,
+    E: 'static,
+    W: Clone + 'static,
+{
+    type Children = BoxedElement<S, M, E, W>;
+
+    type State = W;
+
+    fn lifecycle(
+        &self,
+        lifecycle: Lifecycle<Self>,
+        view_state: &mut Self::State,
+        children: &mut <Self::Children as ElementSeq<S, M, E>>::Storage,
+        _context: &mut CommitContext<S, M, E>,
+    ) {
+        match lifecycle {
+            Lifecycle::Remount | Lifecycle::Update(_) => {
+                *view_state = children.view_state().unwrap().clone();
+            }
+            Lifecycle::Mount | Lifecycle::Unmount => {}
+        }
+    }
+
+    fn build(
+        &self,
+        children: &mut <Self::Children as ElementSeq<S, M, E>>::Storage,
+        _context: &mut CommitContext<S, M, E>,
+    ) -> Self::State {
+        children.view_state().unwrap().clone()
+    }
+}
+
+impl<'event, W> EventTarget<'event> for AnyView<W> {
+    type Event = ();
+}
+
+impl<W> fmt::Debug for AnyView<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AnyView").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::component::HigherOrderComponent;
+    use crate::element::ViewElement;
+    use crate::profiler::{LifecycleProfile, Profiler};
+    use crate::render_loop::RenderLoop;
+    use crate::test_utils::{Container, Label, NullRuntime, TestState};
+
+    type LabelElement = ViewElement<Label, TestState<u32>, u32, ()>;
+
+    type RenderFn = fn(&u32, &mut RenderContext<TestState<u32>>) -> LabelElement;
+
+    type Children = (LabelElement, AnyElement<TestState<u32>, u32, ()>);
+
+    fn child(value: &u32, _context: &mut RenderContext<TestState<u32>>) -> LabelElement {
+        Label(format!("child {}", value)).el(())
+    }
+
+    fn app(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Container<Children>, TestState<u32>, u32, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        Container::new().el((
+            Label(format!("app {}", value)).el(()),
+            AnyElement::new((child as RenderFn).memoize(value / 2)),
+        ))
+    }
+
+    #[test]
+    fn test_memoize() {
+        let profiler = Rc::new(Profiler::new());
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+        render_loop.set_profiler(Some(profiler.clone()));
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount app 0", "mount child 0"]);
+        profiler.take_reports();
+
+        render_loop.push_message(1);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update app 1"]);
+        let reports = profiler.take_reports();
+        assert!(!reports[0]
+            .lifecycles
+            .contains_key(any::type_name::<AnyView<()>>()));
+
+        render_loop.push_message(2);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update app 2", "update child 1"]);
+        let reports = profiler.take_reports();
+        assert_eq!(
+            reports[0].lifecycles[any::type_name::<AnyView<()>>()],
+            LifecycleProfile {
+                updates: 1,
+                ..Default::default()
+            }
+        );
+    }
+}
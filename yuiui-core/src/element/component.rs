@@ -1,6 +1,7 @@
 use crate::component::Component;
 use crate::component_stack::ComponentStack;
 use crate::context::RenderContext;
+use crate::id::NodePath;
 use crate::view_node::{ViewNode, ViewNodeMut};
 
 use super::{Element, ElementSeq};
@@ -19,6 +20,9 @@ impl<C> ComponentElement<C> {
 impl<C, S, M, E> Element<S, M, E> for ComponentElement<C>
 where
     C: Component<S, M, E>,
+    S: 'static,
+    M: 'static,
+    E: 'static,
 {
     type View = <C::Element as Element<S, M, E>>::View;
 
@@ -28,9 +32,11 @@ where
         self,
         context: &mut RenderContext<S>,
     ) -> ViewNode<Self::View, Self::Components, S, M, E> {
-        context.level = Self::Components::LEVEL;
-        let element = self.component.render(context);
-        let node = element.render(context);
+        let mut local_slots = Vec::new();
+        let (element, _) =
+            context.render_component(&self.component, Self::Components::LEVEL, &mut local_slots);
+        let mut node = element.render(context);
+        node.local_states.push(local_slots);
         ViewNode {
             id: node.id,
             view: node.view,
@@ -38,6 +44,7 @@ where
             view_state: node.view_state,
             children: node.children,
             components: (self.component, node.components),
+            local_states: node.local_states,
             dirty: true,
         }
     }
@@ -48,8 +55,24 @@ where
         context: &mut RenderContext<S>,
     ) -> bool {
         let (head_component, tail_components) = node.components;
-        context.level = Self::Components::LEVEL;
-        let element = self.component.render(context);
+        let level = Self::Components::LEVEL;
+        if context.did_timeout() {
+            // The rest of the subtree is rendered later in its own unit of work.
+            *head_component = self.component;
+            context.pending_updates.push(NodePath {
+                id_path: context.id_path().to_vec(),
+                level,
+            });
+            return true;
+        }
+        let (element, has_pending_effects) = context.render_component(
+            &self.component,
+            level,
+            &mut node.local_states[level as usize - 1],
+        );
+        if has_pending_effects {
+            *node.dirty = true;
+        }
         *head_component = self.component;
         let mut node = ViewNodeMut {
             id: node.id,
@@ -58,15 +81,19 @@ where
             view_state: node.view_state,
             children: node.children,
             components: tail_components,
+            local_states: node.local_states,
             dirty: node.dirty,
         };
-        element.update(&mut node, context)
+        element.update(&mut node, context) || has_pending_effects
     }
 }
 
 impl<C, S, M, E> ElementSeq<S, M, E> for ComponentElement<C>
 where
     C: Component<S, M, E>,
+    S: 'static,
+    M: 'static,
+    E: 'static,
 {
     type Storage =
         ViewNode<<Self as Element<S, M, E>>::View, <Self as Element<S, M, E>>::Components, S, M, E>;
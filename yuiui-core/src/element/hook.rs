@@ -7,7 +7,7 @@ use crate::context::{CommitContext, RenderContext};
 use crate::event::{EventTarget, Lifecycle};
 use crate::id::Level;
 use crate::view::View;
-use crate::view_node::{ViewNode, ViewNodeMut};
+use crate::view_node::{CommitMode, ViewNode, ViewNodeMut};
 
 use super::{Element, ElementSeq};
 
@@ -58,6 +58,7 @@ where
             view_state: node.view_state,
             children: node.children,
             components: Hook::new(node.components, self.callback),
+            local_states: node.local_states,
             dirty: node.dirty,
         }
     }
@@ -153,6 +154,10 @@ where
     ) -> Self::State {
         self.inner.build(children, context)
     }
+
+    fn inspect(&self) -> Option<String> {
+        self.inner.inspect()
+    }
 }
 
 impl<'event, Inner, Callback> EventTarget<'event> for Hook<Inner, Callback>
@@ -180,6 +185,20 @@ where
             Inner::force_update(&mut inner_node, level, context)
         })
     }
+
+    fn commit<'a>(
+        node: &mut ViewNodeMut<'a, Self::View, Self, S, M, E>,
+        mode: CommitMode,
+        context: &mut CommitContext<S, M, E>,
+    ) {
+        with_inner_node(node, |mut inner_node| {
+            Inner::commit(&mut inner_node, mode, context)
+        })
+    }
+
+    fn component_names(&self, names: &mut Vec<&'static str>) {
+        self.inner.component_names(names);
+    }
 }
 
 impl<Inner, Callback> fmt::Debug for Hook<Inner, Callback>
@@ -209,6 +228,7 @@ where
         view_state: node.view_state,
         children: node.children,
         components: &mut node.components.inner,
+        local_states: node.local_states,
         dirty: node.dirty,
     };
     let callback = &node.view.callback;
@@ -21,6 +21,9 @@ where
     Hoc: HigherOrderComponent<Deps, S, M, E>,
     Hoc::Component: AsRef<Deps>,
     Deps: PartialEq,
+    S: 'static,
+    M: 'static,
+    E: 'static,
 {
     type View = <<Hoc::Component as Component<S, M, E>>::Element as Element<S, M, E>>::View;
 
@@ -60,6 +63,9 @@ where
     Hoc: HigherOrderComponent<Deps, S, M, E>,
     Hoc::Component: AsRef<Deps>,
     Deps: PartialEq,
+    S: 'static,
+    M: 'static,
+    E: 'static,
 {
     type Storage =
         ViewNode<<Self as Element<S, M, E>>::View, <Self as Element<S, M, E>>::Components, S, M, E>;
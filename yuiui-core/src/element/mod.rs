@@ -1,13 +1,18 @@
 mod adapt;
+mod any;
 mod component;
 mod hook;
 mod memoize;
+mod provider;
+mod result;
 mod view;
 
 pub use adapt::AdaptElement;
+pub use any::{AnyElement, AnyView};
 pub use component::ComponentElement;
 pub use hook::{HookCallback, HookElement};
 pub use memoize::MemoizeElement;
+pub use provider::ProviderElement;
 pub use view::ViewElement;
 
 use std::fmt;
@@ -49,6 +54,13 @@ pub trait Element<S, M, E>:
     {
         HookElement::new(self, callback)
     }
+
+    fn provide<T>(self, value: T) -> ProviderElement<Self, T>
+    where
+        T: PartialEq + 'static,
+    {
+        ProviderElement::new(self, value)
+    }
 }
 
 pub trait ElementSeq<S, M, E> {
@@ -0,0 +1,61 @@
+use crate::context::RenderContext;
+use crate::view_node::{ViewNode, ViewNodeMut};
+
+use super::{Element, ElementSeq};
+
+#[derive(Debug)]
+pub struct ProviderElement<Inner, T> {
+    inner: Inner,
+    value: T,
+}
+
+impl<Inner, T> ProviderElement<Inner, T> {
+    #[inline]
+    pub const fn new(inner: Inner, value: T) -> Self {
+        Self { inner, value }
+    }
+}
+
+impl<Inner, T, S, M, E> Element<S, M, E> for ProviderElement<Inner, T>
+where
+    Inner: Element<S, M, E>,
+    T: PartialEq + 'static,
+{
+    type View = Inner::View;
+
+    type Components = Inner::Components;
+
+    fn render(
+        self,
+        context: &mut RenderContext<S>,
+    ) -> ViewNode<Self::View, Self::Components, S, M, E> {
+        context.providers.provide(context.id_stack.id(), self.value);
+        self.inner.render(context)
+    }
+
+    fn update(
+        self,
+        node: &mut ViewNodeMut<Self::View, Self::Components, S, M, E>,
+        context: &mut RenderContext<S>,
+    ) -> bool {
+        context.providers.provide(node.id, self.value);
+        self.inner.update(node, context)
+    }
+}
+
+impl<Inner, T, S, M, E> ElementSeq<S, M, E> for ProviderElement<Inner, T>
+where
+    Inner: Element<S, M, E>,
+    T: PartialEq + 'static,
+{
+    type Storage =
+        ViewNode<<Self as Element<S, M, E>>::View, <Self as Element<S, M, E>>::Components, S, M, E>;
+
+    fn render_children(self, context: &mut RenderContext<S>) -> Self::Storage {
+        context.render_node(self)
+    }
+
+    fn update_children(self, storage: &mut Self::Storage, context: &mut RenderContext<S>) -> bool {
+        context.update_node(self, storage)
+    }
+}
@@ -0,0 +1,53 @@
+use std::panic;
+
+use crate::context::RenderContext;
+use crate::error::RenderError;
+use crate::view_node::{ViewNode, ViewNodeMut};
+
+use super::{Element, ElementSeq};
+
+impl<T, S, M, E> Element<S, M, E> for Result<T, RenderError>
+where
+    T: Element<S, M, E>,
+{
+    type View = T::View;
+
+    type Components = T::Components;
+
+    fn render(
+        self,
+        context: &mut RenderContext<S>,
+    ) -> ViewNode<Self::View, Self::Components, S, M, E> {
+        match self {
+            Ok(element) => element.render(context),
+            Err(error) => panic::resume_unwind(Box::new(error)),
+        }
+    }
+
+    fn update(
+        self,
+        node: &mut ViewNodeMut<Self::View, Self::Components, S, M, E>,
+        context: &mut RenderContext<S>,
+    ) -> bool {
+        match self {
+            Ok(element) => element.update(node, context),
+            Err(error) => panic::resume_unwind(Box::new(error)),
+        }
+    }
+}
+
+impl<T, S, M, E> ElementSeq<S, M, E> for Result<T, RenderError>
+where
+    T: Element<S, M, E>,
+{
+    type Storage =
+        ViewNode<<Self as Element<S, M, E>>::View, <Self as Element<S, M, E>>::Components, S, M, E>;
+
+    fn render_children(self, context: &mut RenderContext<S>) -> Self::Storage {
+        context.render_node(self)
+    }
+
+    fn update_children(self, storage: &mut Self::Storage, context: &mut RenderContext<S>) -> bool {
+        context.update_node(self, storage)
+    }
+}
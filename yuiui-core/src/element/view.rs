@@ -35,8 +35,7 @@ where
     ) -> ViewNode<Self::View, Self::Components, S, M, E> {
         let id = context.id_stack.id();
         let children = self.children.render_children(context);
-        let node = ViewNode::new(id, self.view, children, ComponentTermination::new());
-        node
+        ViewNode::new(id, self.view, children, ComponentTermination::new())
     }
 
     fn update(
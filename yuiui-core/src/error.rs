@@ -0,0 +1,41 @@
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RenderError {
+    message: String,
+}
+
+impl RenderError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub(crate) fn from_panic(payload: Box<dyn Any + Send>) -> Self {
+        match payload.downcast::<Self>() {
+            Ok(error) => *error,
+            Err(payload) => match payload.downcast::<String>() {
+                Ok(message) => Self::new(*message),
+                Err(payload) => match payload.downcast::<&'static str>() {
+                    Ok(message) => Self::new(*message),
+                    Err(_) => Self::new("Box<dyn Any>"),
+                },
+            },
+        }
+    }
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for RenderError {}
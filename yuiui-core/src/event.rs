@@ -25,6 +25,7 @@ impl<'event, T: 'static> Event<'event> for &'event T {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EventDestination {
     Unicast(IdPathBuf),
     Multicast(Vec<IdPathBuf>),
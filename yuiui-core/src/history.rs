@@ -0,0 +1,512 @@
+use std::collections::VecDeque;
+use std::mem;
+use std::ops::Deref;
+
+use crate::command::{Commands, Subscriptions};
+use crate::state::{Atom, Effect, State};
+
+#[derive(Debug)]
+pub enum HistoryMessage<M> {
+    Apply(M),
+    // Applies the message without recording it, for commands spawned while undoing or redoing.
+    Untracked(M),
+    Undo,
+    Redo,
+    BeginTransaction,
+    CommitTransaction,
+    Clear,
+}
+
+impl<M> From<M> for HistoryMessage<M> {
+    fn from(message: M) -> Self {
+        Self::Apply(message)
+    }
+}
+
+pub trait HistoryStrategy<S: State> {
+    type Entry;
+
+    fn record(&mut self, state: &S, message: &S::Message) -> Self::Entry;
+
+    fn revert(
+        &mut self,
+        state: &mut S,
+        entry: Self::Entry,
+        commands: &mut Commands<S::Message>,
+    ) -> (Self::Entry, Effect);
+}
+
+#[derive(Debug, Default)]
+pub struct Snapshot;
+
+impl<S> HistoryStrategy<S> for Snapshot
+where
+    S: State + Clone,
+{
+    type Entry = S;
+
+    fn record(&mut self, state: &S, _message: &S::Message) -> Self::Entry {
+        state.clone()
+    }
+
+    fn revert(
+        &mut self,
+        state: &mut S,
+        entry: Self::Entry,
+        _commands: &mut Commands<S::Message>,
+    ) -> (Self::Entry, Effect) {
+        // The snapshot does not carry the subscribers of the current state, so every subscribed
+        // node is rendered again by the forced update to subscribe to the restored atoms.
+        (mem::replace(state, entry), Effect::ForceUpdate)
+    }
+}
+
+pub trait Invertible: State {
+    fn invert(&self, message: &Self::Message) -> Self::Message;
+}
+
+#[derive(Debug, Default)]
+pub struct Inverse;
+
+impl<S> HistoryStrategy<S> for Inverse
+where
+    S: Invertible,
+{
+    type Entry = S::Message;
+
+    fn record(&mut self, state: &S, message: &S::Message) -> Self::Entry {
+        state.invert(message)
+    }
+
+    fn revert(
+        &mut self,
+        state: &mut S,
+        entry: Self::Entry,
+        commands: &mut Commands<S::Message>,
+    ) -> (Self::Entry, Effect) {
+        let inverse = state.invert(&entry);
+        let effect = state.update(entry, commands);
+        (inverse, effect)
+    }
+}
+
+#[derive(Debug)]
+pub struct History<S: State, H: HistoryStrategy<S> = Snapshot> {
+    state: S,
+    strategy: H,
+    undo_stack: VecDeque<Vec<H::Entry>>,
+    redo_stack: Vec<Vec<H::Entry>>,
+    transaction: Option<Vec<H::Entry>>,
+    limit: usize,
+    can_undo: Atom<bool>,
+    can_redo: Atom<bool>,
+}
+
+impl<S, H> History<S, H>
+where
+    S: State,
+    H: HistoryStrategy<S>,
+{
+    pub fn new(state: S, strategy: H) -> Self {
+        Self {
+            state,
+            strategy,
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            transaction: None,
+            limit: usize::MAX,
+            can_undo: Atom::new(false),
+            can_redo: Atom::new(false),
+        }
+    }
+
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit;
+        self
+    }
+
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn into_state(self) -> S {
+        self.state
+    }
+
+    pub fn can_undo(&self) -> &Atom<bool> {
+        &self.can_undo
+    }
+
+    pub fn can_redo(&self) -> &Atom<bool> {
+        &self.can_redo
+    }
+
+    fn apply(&mut self, message: S::Message, commands: &mut Commands<S::Message>) -> Effect {
+        let entry = self.strategy.record(&self.state, &message);
+        let effect = self.state.update(message, commands);
+        match &mut self.transaction {
+            Some(transaction) => transaction.push(entry),
+            None => self.push_undo(vec![entry]),
+        }
+        self.redo_stack.clear();
+        effect
+    }
+
+    fn commit_transaction(&mut self) {
+        if let Some(transaction) = self.transaction.take() {
+            if !transaction.is_empty() {
+                self.push_undo(transaction);
+            }
+        }
+    }
+
+    fn push_undo(&mut self, entries: Vec<H::Entry>) {
+        if self.limit == 0 {
+            return;
+        }
+        if self.undo_stack.len() >= self.limit {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(entries);
+    }
+
+    fn revert(
+        &mut self,
+        entries: Vec<H::Entry>,
+        commands: &mut Commands<S::Message>,
+    ) -> (Vec<H::Entry>, Effect) {
+        let mut reverted = Vec::with_capacity(entries.len());
+        let mut effect = Effect::Nop;
+        for entry in entries.into_iter().rev() {
+            let (reverse_entry, reverse_effect) =
+                self.strategy.revert(&mut self.state, entry, commands);
+            reverted.push(reverse_entry);
+            effect = effect.compose(reverse_effect);
+        }
+        (reverted, effect)
+    }
+
+    fn sync_flags(&mut self) -> Effect {
+        let can_undo = !self.undo_stack.is_empty();
+        let can_redo = !self.redo_stack.is_empty();
+        let mut effect = Effect::Nop;
+        if *self.can_undo.get() != can_undo {
+            effect = effect.compose(self.can_undo.set(can_undo));
+        }
+        if *self.can_redo.get() != can_redo {
+            effect = effect.compose(self.can_redo.set(can_redo));
+        }
+        effect
+    }
+}
+
+impl<S, H> Deref for History<S, H>
+where
+    S: State,
+    H: HistoryStrategy<S>,
+{
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.state
+    }
+}
+
+impl<S, H> State for History<S, H>
+where
+    S: State,
+    S::Message: 'static,
+    H: HistoryStrategy<S>,
+{
+    type Message = HistoryMessage<S::Message>;
+
+    fn init(&mut self, commands: &mut Commands<Self::Message>) -> Effect {
+        let mut inner_commands = Commands::new();
+        let effect = self.state.init(&mut inner_commands);
+        commands.append(&mut inner_commands.map(HistoryMessage::Apply));
+        effect
+    }
+
+    fn update(&mut self, message: Self::Message, commands: &mut Commands<Self::Message>) -> Effect {
+        let mut inner_commands = Commands::new();
+        let is_tracked = matches!(message, HistoryMessage::Apply(_));
+        let effect = match message {
+            HistoryMessage::Apply(message) => self.apply(message, &mut inner_commands),
+            HistoryMessage::Untracked(message) => self.state.update(message, &mut inner_commands),
+            HistoryMessage::Undo => {
+                self.commit_transaction();
+                if let Some(entries) = self.undo_stack.pop_back() {
+                    let (entries, effect) = self.revert(entries, &mut inner_commands);
+                    self.redo_stack.push(entries);
+                    effect
+                } else {
+                    Effect::Nop
+                }
+            }
+            HistoryMessage::Redo => {
+                self.commit_transaction();
+                if let Some(entries) = self.redo_stack.pop() {
+                    let (entries, effect) = self.revert(entries, &mut inner_commands);
+                    self.push_undo(entries);
+                    effect
+                } else {
+                    Effect::Nop
+                }
+            }
+            HistoryMessage::BeginTransaction => {
+                self.commit_transaction();
+                self.transaction = Some(Vec::new());
+                Effect::Nop
+            }
+            HistoryMessage::CommitTransaction => {
+                self.commit_transaction();
+                Effect::Nop
+            }
+            HistoryMessage::Clear => {
+                self.undo_stack.clear();
+                self.redo_stack.clear();
+                self.transaction = None;
+                Effect::Nop
+            }
+        };
+        // Commands of a replayed message must not be recorded as new entries when they resolve.
+        if is_tracked {
+            commands.append(&mut inner_commands.map(HistoryMessage::Apply));
+        } else {
+            commands.append(&mut inner_commands.map(HistoryMessage::Untracked));
+        }
+        effect.compose(self.sync_flags())
+    }
+
+    fn subscriptions(&self) -> Subscriptions<Self::Message> {
+        self.state.subscriptions().map(HistoryMessage::Apply)
+    }
+
+    fn derive(&mut self) -> Effect {
+        self.state.derive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use std::time::Duration;
+
+    use super::*;
+    use crate::command::Command;
+    use crate::component::HigherOrderComponent;
+    use crate::context::RenderContext;
+    use crate::element::{MemoizeElement, ViewElement};
+    use crate::render_loop::RenderLoop;
+    use crate::test_utils::{Container, NullRuntime};
+    use crate::view::View;
+
+    #[derive(Clone, Debug, Default)]
+    struct Counter {
+        count: Atom<i64>,
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl State for Counter {
+        type Message = i64;
+
+        fn update(
+            &mut self,
+            delta: Self::Message,
+            _commands: &mut Commands<Self::Message>,
+        ) -> Effect {
+            self.count.update(|count| *count += delta)
+        }
+    }
+
+    impl Invertible for Counter {
+        fn invert(&self, delta: &Self::Message) -> Self::Message {
+            -delta
+        }
+    }
+
+    fn update<S: State>(state: &mut S, message: S::Message) -> Effect {
+        state.update(message, &mut Commands::new())
+    }
+
+    #[test]
+    fn test_history() {
+        let mut history = History::new(Counter::default(), Inverse);
+
+        update(&mut history, HistoryMessage::Apply(1));
+        update(&mut history, HistoryMessage::BeginTransaction);
+        update(&mut history, HistoryMessage::Apply(2));
+        update(&mut history, HistoryMessage::Apply(3));
+        update(&mut history, HistoryMessage::CommitTransaction);
+        assert_eq!(*history.count.get(), 6);
+        assert!(*history.can_undo().get());
+
+        assert!(matches!(
+            update(&mut history, HistoryMessage::Undo),
+            Effect::Update(_)
+        ));
+        assert_eq!(*history.count.get(), 1);
+        assert!(*history.can_redo().get());
+
+        update(&mut history, HistoryMessage::Undo);
+        assert_eq!(*history.count.get(), 0);
+        assert!(!*history.can_undo().get());
+
+        update(&mut history, HistoryMessage::Redo);
+        update(&mut history, HistoryMessage::Redo);
+        assert_eq!(*history.count.get(), 6);
+        assert!(!*history.can_redo().get());
+
+        let mut history = History::new(Counter::default(), Snapshot).with_limit(1);
+        update(&mut history, HistoryMessage::Apply(1));
+        update(&mut history, HistoryMessage::Apply(2));
+        assert!(matches!(
+            update(&mut history, HistoryMessage::Undo),
+            Effect::ForceUpdate
+        ));
+        assert_eq!(*history.count.get(), 1);
+        assert!(matches!(
+            update(&mut history, HistoryMessage::Undo),
+            Effect::Nop
+        ));
+        update(&mut history, HistoryMessage::Redo);
+        assert_eq!(*history.count.get(), 3);
+
+        let mut history = History::new(Counter::default(), Snapshot).with_limit(0);
+        update(&mut history, HistoryMessage::Apply(1));
+        assert_eq!(*history.count.get(), 1);
+        assert!(!*history.can_undo().get());
+    }
+
+    #[derive(Debug, Default)]
+    struct Document {
+        count: Atom<i64>,
+        saves: usize,
+    }
+
+    #[derive(Debug)]
+    enum DocumentMessage {
+        Add(i64),
+        Saved,
+    }
+
+    impl State for Document {
+        type Message = DocumentMessage;
+
+        fn update(
+            &mut self,
+            message: Self::Message,
+            commands: &mut Commands<Self::Message>,
+        ) -> Effect {
+            match message {
+                DocumentMessage::Add(delta) => {
+                    commands.push(Command::delay(Duration::ZERO, || DocumentMessage::Saved));
+                    self.count.update(|count| *count += delta)
+                }
+                DocumentMessage::Saved => {
+                    self.saves += 1;
+                    Effect::Nop
+                }
+            }
+        }
+    }
+
+    impl Invertible for Document {
+        fn invert(&self, message: &Self::Message) -> Self::Message {
+            match message {
+                DocumentMessage::Add(delta) => DocumentMessage::Add(-delta),
+                DocumentMessage::Saved => DocumentMessage::Saved,
+            }
+        }
+    }
+
+    fn resolve<T>(commands: Commands<T>) -> Vec<T> {
+        commands
+            .entries
+            .into_iter()
+            .map(|(command, _)| match command {
+                Command::Timeout(_, callback) => callback(),
+                command => panic!("unexpected command: {}", command.kind()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_commands_of_undo_and_redo() {
+        let mut history = History::new(Document::default(), Inverse);
+
+        let mut commands = Commands::new();
+        history.update(
+            HistoryMessage::Apply(DocumentMessage::Add(1)),
+            &mut commands,
+        );
+        assert_eq!(commands.len(), 1);
+
+        let mut commands = Commands::new();
+        history.update(HistoryMessage::Undo, &mut commands);
+        assert_eq!(*history.count.get(), 0);
+        for message in resolve(commands) {
+            assert!(matches!(
+                message,
+                HistoryMessage::Untracked(DocumentMessage::Saved)
+            ));
+            update(&mut history, message);
+        }
+        assert_eq!(history.saves, 1);
+        assert!(*history.can_redo().get());
+
+        update(&mut history, HistoryMessage::Redo);
+        assert_eq!(*history.count.get(), 1);
+        assert!(*history.can_undo().get());
+    }
+
+    type CountFn = fn(
+        &(),
+        &mut RenderContext<History<Counter>>,
+    ) -> ViewElement<Container<()>, History<Counter>, HistoryMessage<i64>, ()>;
+
+    type AppContainer = Container<(MemoizeElement<CountFn, ()>,)>;
+
+    fn count(
+        _props: &(),
+        context: &mut RenderContext<History<Counter>>,
+    ) -> ViewElement<Container<()>, History<Counter>, HistoryMessage<i64>, ()> {
+        let count = *context.use_atom(|state| &state.count);
+        context
+            .state()
+            .log
+            .borrow_mut()
+            .push(format!("render {}", count));
+        Container::new().el(())
+    }
+
+    fn app(
+        _props: &(),
+        _context: &mut RenderContext<History<Counter>>,
+    ) -> ViewElement<AppContainer, History<Counter>, HistoryMessage<i64>, ()> {
+        Container::new().el(((count as CountFn).memoize(()),))
+    }
+
+    #[test]
+    fn test_snapshot_keeps_memoized_subscribers() {
+        let mut state = History::new(Counter::default(), Snapshot);
+        let log = state.log.clone();
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(log.take(), vec!["render 0"]);
+
+        render_loop.push_message(HistoryMessage::Apply(1));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(log.take(), vec!["render 1"]);
+
+        render_loop.push_message(HistoryMessage::Undo);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(log.take(), vec!["render 0"]);
+
+        render_loop.push_message(HistoryMessage::Apply(2));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(log.take(), vec!["render 2"]);
+    }
+}
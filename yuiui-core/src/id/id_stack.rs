@@ -32,6 +32,10 @@ impl IdStack {
         self.id_path.pop();
     }
 
+    pub(crate) fn truncate(&mut self, len: usize) {
+        self.id_path.truncate(len);
+    }
+
     pub fn next(&mut self) -> Id {
         let id = self.next_id;
         self.next_id = id.next();
@@ -1,4 +1,5 @@
 use std::collections::VecDeque;
+use std::mem;
 
 use super::{Id, IdPath, IdPathBuf};
 
@@ -22,6 +23,11 @@ impl<T> IdTree<T> {
         Self { arena, len: 0 }
     }
 
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
     #[inline]
     pub fn is_empty(&self) -> bool {
         self.len == 0
@@ -32,6 +38,10 @@ impl<T> IdTree<T> {
         Cursor::new(&self.arena, 0)
     }
 
+    pub fn overlaps<U>(&self, other: &IdTree<U>) -> bool {
+        self.root().overlaps(other.root())
+    }
+
     pub fn insert(&mut self, id_path: &IdPath, data: T) {
         let key = self.insertion_point(id_path);
         let node = &mut self.arena[key];
@@ -53,6 +63,49 @@ impl<T> IdTree<T> {
         node.data = Some(data);
     }
 
+    pub fn into_outermost_subtrees(
+        mut self,
+        is_detached: impl Fn(Id) -> bool,
+    ) -> VecDeque<IdTree<T>> {
+        let mut subtrees = VecDeque::new();
+        let mut stack = vec![(0, Vec::new())];
+
+        while let Some((key, id_path)) = stack.pop() {
+            if self.arena[key].data.is_some() {
+                let mut subtree = IdTree::new();
+                let mut subtree_stack = vec![(key, id_path)];
+                while let Some((key, id_path)) = subtree_stack.pop() {
+                    let node = &mut self.arena[key];
+                    let children = mem::take(&mut node.children);
+                    if let Some(data) = node.data.take() {
+                        subtree.insert(&id_path, data);
+                    }
+                    for &child in children.iter().rev() {
+                        let child_id = self.arena[child].id;
+                        let mut child_path = id_path.clone();
+                        child_path.push(child_id);
+                        // Detached top-level nodes (e.g. other roots) are not covered by the root.
+                        if key == 0 && is_detached(child_id) {
+                            stack.push((child, child_path));
+                        } else {
+                            subtree_stack.push((child, child_path));
+                        }
+                    }
+                }
+                subtrees.push_back(subtree);
+            } else {
+                let children = mem::take(&mut self.arena[key].children);
+                for &child in children.iter().rev() {
+                    let mut child_path = id_path.clone();
+                    child_path.push(self.arena[child].id);
+                    stack.push((child, child_path));
+                }
+            }
+        }
+
+        subtrees
+    }
+
     fn insertion_point(&mut self, mut id_path: &IdPath) -> usize {
         let mut key = 0;
 
@@ -145,6 +198,15 @@ pub struct Cursor<'a, T> {
     node: &'a Node<T>,
 }
 
+impl<'a, T> Clone for Cursor<'a, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Cursor<'a, T> {}
+
 impl<'a, T> Cursor<'a, T> {
     fn new(arena: &'a [Node<T>], key: usize) -> Self {
         Self {
@@ -155,7 +217,7 @@ impl<'a, T> Cursor<'a, T> {
 
     #[inline]
     pub fn current(&self) -> &Node<T> {
-        &self.node
+        self.node
     }
 
     #[inline]
@@ -167,6 +229,20 @@ impl<'a, T> Cursor<'a, T> {
     pub fn descendants(&self) -> Descendants<'a, T> {
         Descendants::new(&self.node.children, self.arena)
     }
+
+    // Every node leads to some data, so two trees overlap once one of them has data at a node
+    // that both of them reach.
+    pub fn overlaps<U>(&self, other: Cursor<'_, U>) -> bool {
+        if self.node.data.is_some() || other.node.data.is_some() {
+            return true;
+        }
+        self.children().any(|child| {
+            other
+                .children()
+                .find(|other_child| other_child.node.id == child.node.id)
+                .is_some_and(|other_child| child.overlaps(other_child))
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -209,7 +285,7 @@ pub struct Descendants<'a, T> {
 
 impl<'a, T> Descendants<'a, T> {
     fn new(children: &'a [usize], arena: &'a [Node<T>]) -> Self {
-        let queue = VecDeque::from_iter(children.into_iter().copied());
+        let queue = VecDeque::from_iter(children.iter().copied());
         Self { queue, arena }
     }
 }
@@ -287,4 +363,53 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_into_outermost_subtrees() {
+        let id_tree = IdTree::from_iter([
+            (vec![], 1),
+            (vec![Id::new(2)], 2),
+            (vec![Id::new(2), Id::new(5)], 3),
+            (vec![Id::new(3), Id::new(6)], 4),
+        ]);
+        let subtrees = id_tree
+            .into_outermost_subtrees(|id| id == Id::new(2))
+            .into_iter()
+            .map(|subtree| {
+                let cursor = subtree.root();
+                let mut entries = cursor
+                    .current()
+                    .data()
+                    .copied()
+                    .into_iter()
+                    .collect::<Vec<_>>();
+                entries.extend(
+                    cursor
+                        .descendants()
+                        .filter_map(|cursor| cursor.current().data().copied()),
+                );
+                entries
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(subtrees, vec![vec![1, 4], vec![2, 3]]);
+    }
+
+    #[test]
+    fn test_overlaps() {
+        let id_tree = IdTree::from_iter([
+            (vec![Id::new(2), Id::new(5)], ()),
+            (vec![Id::new(3), Id::new(6)], ()),
+        ]);
+        let ancestor = IdTree::from_iter([(vec![Id::new(2)], ())]);
+        let descendant = IdTree::from_iter([(vec![Id::new(3), Id::new(6), Id::new(8)], ())]);
+        let sibling = IdTree::from_iter([(vec![Id::new(3), Id::new(7)], ())]);
+        let disjoint = IdTree::from_iter([(vec![Id::new(4)], ())]);
+        let whole = IdTree::from_iter([(vec![], ())]);
+        assert!(id_tree.overlaps(&ancestor));
+        assert!(id_tree.overlaps(&descendant));
+        assert!(!id_tree.overlaps(&sibling));
+        assert!(!id_tree.overlaps(&disjoint));
+        assert!(id_tree.overlaps(&whole));
+        assert!(!id_tree.overlaps(&IdTree::<()>::new()));
+    }
 }
@@ -8,14 +8,14 @@ pub use id_tree::IdTree;
 use std::num::NonZeroU32;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Id(NonZeroU32);
 
 impl Id {
-    pub const ROOT: Self = Self(unsafe { NonZeroU32::new_unchecked(1) });
+    pub const ROOT: Self = Self::new(1);
 
     const fn new(id: u32) -> Self {
-        assert!(id > 0);
-        Self(unsafe { NonZeroU32::new_unchecked(id) })
+        Self(NonZeroU32::new(id).unwrap())
     }
 
     pub fn is_root(&self) -> bool {
@@ -33,19 +33,20 @@ impl<'a> From<&'a IdPath> for Id {
     }
 }
 
-impl Into<NonZeroU32> for Id {
-    fn into(self) -> NonZeroU32 {
-        self.0
+impl From<Id> for NonZeroU32 {
+    fn from(id: Id) -> Self {
+        id.0
     }
 }
 
-impl Into<u32> for Id {
-    fn into(self) -> u32 {
-        self.0.get()
+impl From<Id> for u32 {
+    fn from(id: Id) -> Self {
+        id.0.get()
     }
 }
 
 #[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NodePath {
     pub id_path: IdPathBuf,
     pub level: Level,
@@ -0,0 +1,33 @@
+use std::fmt;
+
+use crate::id::{Id, IdPathBuf};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Inspection {
+    pub node: InspectedNode,
+    pub roots: Vec<InspectedNode>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InspectedNode {
+    pub id: Id,
+    pub id_path: IdPathBuf,
+    pub view: String,
+    pub components: Vec<String>,
+    pub dirty: bool,
+    pub mounted: bool,
+    pub pending: bool,
+    pub props: Option<String>,
+    pub children: Vec<InspectedNode>,
+}
+
+// Lets views that implement `Debug` return `self.inspect_debug()` from `View::inspect`.
+pub trait InspectDebug: fmt::Debug {
+    fn inspect_debug(&self) -> Option<String> {
+        Some(format!("{:?}", self))
+    }
+}
+
+impl<T: fmt::Debug + ?Sized> InspectDebug for T {}
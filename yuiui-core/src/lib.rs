@@ -1,31 +1,71 @@
+mod atom_collection;
 mod cancellation_token;
 mod command;
 mod component;
 mod component_stack;
 mod context;
+mod deadline;
+mod effect;
+mod either;
 mod element;
+mod error;
 mod event;
+mod history;
 mod hlist;
 mod id;
+mod inspector;
+mod local_state;
+mod middleware;
+#[cfg(feature = "serde")]
+mod persistence;
+mod priority;
+mod profiler;
+mod provider;
+#[cfg(feature = "serde")]
+mod recording;
 mod render_loop;
 mod state;
 mod storages;
+mod subscription;
+#[cfg(test)]
+mod test_utils;
 mod view;
 mod view_node;
 
+pub use atom_collection::{AtomMap, AtomVec};
 pub use cancellation_token::{CancellationToken, RawToken, RawTokenVTable};
-pub use command::{Command, CommandRuntime};
+pub use command::{Command, CommandRuntime, Commands, Subscriptions};
 pub use component::{Component, FunctionComponent, HigherOrderComponent};
 pub use component_stack::ComponentStack;
 pub use context::{CommitContext, RenderContext};
+pub use deadline::{
+    Budget, Deadline, Forever, FrameDeadline, InputPending, PendingWork, StepBudget,
+};
+pub use effect::EffectCleanup;
+pub use either::Either;
 pub use element::{
-    ComponentElement, DebuggableElement, Element, ElementSeq, HookElement, MemoizeElement,
-    ViewElement,
+    AnyElement, AnyView, ComponentElement, DebuggableElement, Element, ElementSeq, HookElement,
+    MemoizeElement, ProviderElement, ViewElement,
 };
+pub use error::RenderError;
 pub use event::{Event, EventDestination, EventPayload, EventTarget, Lifecycle};
+pub use history::{History, HistoryMessage, HistoryStrategy, Inverse, Invertible, Snapshot};
 pub use hlist::{HCons, HList, HNil};
 pub use id::{Id, IdPath, IdPathBuf, Level, NodePath};
+pub use inspector::{InspectDebug, InspectedNode, Inspection};
+pub use local_state::{LocalReducer, LocalState};
+pub use middleware::Middleware;
+#[cfg(feature = "serde")]
+pub use persistence::{decode, encode, Persistence, PersistenceError, Persistent};
+pub use priority::Priority;
+pub use profiler::{ComponentProfile, LifecycleProfile, Phase, ProfileReport, Profiler};
+#[cfg(feature = "serde")]
+pub use recording::{PayloadRegistry, Recorder, RecordingError, Session, SessionRecord};
 pub use render_loop::{RenderFlow, RenderLoop};
-pub use state::{Atom, Effect, State};
+pub use state::{Atom, Dependency, Effect, Selector, State};
+pub use storages::{
+    BoxedElement, BoxedStorage, ErrorBoundary, ErrorBoundaryStorage, Keyed, Portal, PortalStorage,
+    PortalTarget, Suspense, SuspenseStorage,
+};
 pub use view::View;
 pub use view_node::{CommitMode, Traversable, ViewNode, ViewNodeMut, ViewNodeSeq, Visitor};
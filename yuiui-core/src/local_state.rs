@@ -0,0 +1,243 @@
+use std::any::{self, Any};
+use std::cell::{Ref, RefCell};
+use std::fmt;
+use std::rc::Rc;
+
+use crate::context::CommitContext;
+use crate::id::NodePath;
+
+pub(crate) type LocalSlots = Vec<Rc<dyn Any>>;
+
+#[derive(Default)]
+pub(crate) struct LocalSlotCursor {
+    slots: LocalSlots,
+    index: usize,
+    has_pending_effects: bool,
+}
+
+impl LocalSlotCursor {
+    pub(crate) fn new(slots: LocalSlots) -> Self {
+        Self {
+            slots,
+            index: 0,
+            has_pending_effects: false,
+        }
+    }
+
+    pub(crate) fn next<T, F>(&mut self, f: F) -> Rc<RefCell<T>>
+    where
+        T: 'static,
+        F: FnOnce() -> T,
+    {
+        let index = self.index;
+        self.index += 1;
+        if let Some(slot) = self.slots.get(index) {
+            slot.clone().downcast().unwrap_or_else(|_| {
+                panic!(
+                    "The local state at slot {} is not {}. Local states must be used in the same order on every render.",
+                    index,
+                    any::type_name::<T>()
+                )
+            })
+        } else {
+            let cell = Rc::new(RefCell::new(f()));
+            self.slots.push(cell.clone());
+            cell
+        }
+    }
+
+    pub(crate) fn schedule_effect(&mut self) {
+        self.has_pending_effects = true;
+    }
+
+    pub(crate) fn into_slots(self) -> (LocalSlots, bool) {
+        (self.slots, self.has_pending_effects)
+    }
+}
+
+impl fmt::Debug for LocalSlotCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LocalSlotCursor")
+            .field("len", &self.slots.len())
+            .field("index", &self.index)
+            .field("has_pending_effects", &self.has_pending_effects)
+            .finish()
+    }
+}
+
+pub struct LocalState<T> {
+    cell: Rc<RefCell<T>>,
+    node_path: NodePath,
+}
+
+impl<T> LocalState<T> {
+    pub(crate) fn new(cell: Rc<RefCell<T>>, node_path: NodePath) -> Self {
+        Self { cell, node_path }
+    }
+
+    #[inline]
+    pub fn get(&self) -> Ref<'_, T> {
+        self.cell.borrow()
+    }
+
+    #[inline]
+    pub fn node_path(&self) -> &NodePath {
+        &self.node_path
+    }
+
+    pub fn set<S, M, E>(&self, new_value: T, context: &mut CommitContext<S, M, E>) {
+        *self.cell.borrow_mut() = new_value;
+        context.pending_updates.push(self.node_path.clone());
+    }
+
+    pub fn update<F, S, M, E>(&self, f: F, context: &mut CommitContext<S, M, E>)
+    where
+        F: FnOnce(&mut T),
+    {
+        f(&mut self.cell.borrow_mut());
+        context.pending_updates.push(self.node_path.clone());
+    }
+
+    pub fn update_if<F, S, M, E>(&self, f: F, context: &mut CommitContext<S, M, E>)
+    where
+        F: FnOnce(&mut T) -> bool,
+    {
+        if f(&mut self.cell.borrow_mut()) {
+            context.pending_updates.push(self.node_path.clone());
+        }
+    }
+}
+
+impl<T> Clone for LocalState<T> {
+    fn clone(&self) -> Self {
+        Self {
+            cell: self.cell.clone(),
+            node_path: self.node_path.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LocalState<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LocalState")
+            .field("value", &self.cell.borrow())
+            .field("node_path", &self.node_path)
+            .finish()
+    }
+}
+
+pub struct LocalReducer<T, A> {
+    state: LocalState<T>,
+    reducer: fn(&mut T, A) -> bool,
+}
+
+impl<T, A> LocalReducer<T, A> {
+    pub(crate) fn new(state: LocalState<T>, reducer: fn(&mut T, A) -> bool) -> Self {
+        Self { state, reducer }
+    }
+
+    #[inline]
+    pub fn get(&self) -> Ref<'_, T> {
+        self.state.get()
+    }
+
+    #[inline]
+    pub fn node_path(&self) -> &NodePath {
+        self.state.node_path()
+    }
+
+    pub fn dispatch<S, M, E>(&self, action: A, context: &mut CommitContext<S, M, E>) {
+        let reducer = self.reducer;
+        self.state
+            .update_if(move |value| reducer(value, action), context);
+    }
+}
+
+impl<T, A> Clone for LocalReducer<T, A> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            reducer: self.reducer,
+        }
+    }
+}
+
+impl<T: fmt::Debug, A> fmt::Debug for LocalReducer<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LocalReducer")
+            .field("state", &self.state)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::HigherOrderComponent;
+    use crate::context::RenderContext;
+    use crate::element::{ElementSeq, ViewElement};
+    use crate::event::{EventTarget, Lifecycle};
+    use crate::render_loop::RenderLoop;
+    use crate::test_utils::{NullRuntime, TestState};
+    use crate::view::View;
+
+    struct Counter {
+        count: u32,
+        increment: LocalReducer<u32, u32>,
+    }
+
+    impl View<TestState<()>, (), ()> for Counter {
+        type Children = ();
+
+        type State = ();
+
+        fn lifecycle(
+            &self,
+            _lifecycle: Lifecycle<Self>,
+            _view_state: &mut Self::State,
+            _children: &mut <Self::Children as ElementSeq<TestState<()>, (), ()>>::Storage,
+            context: &mut CommitContext<TestState<()>, (), ()>,
+        ) {
+            context.state().push_log(self.count.to_string());
+            self.increment.dispatch(1, context);
+        }
+
+        fn build(
+            &self,
+            _children: &mut <Self::Children as ElementSeq<TestState<()>, (), ()>>::Storage,
+            _context: &mut CommitContext<TestState<()>, (), ()>,
+        ) -> Self::State {
+        }
+    }
+
+    impl<'event> EventTarget<'event> for Counter {
+        type Event = ();
+    }
+
+    fn increment(count: &mut u32, n: u32) -> bool {
+        if *count < 3 {
+            *count += n;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn counter(
+        _props: &(),
+        context: &mut RenderContext<TestState<()>>,
+    ) -> ViewElement<Counter, TestState<()>, (), ()> {
+        let increment = context.use_reducer(|| 0, increment);
+        let count = *increment.get();
+        Counter { count, increment }.el(())
+    }
+
+    #[test]
+    fn test_use_reducer() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(counter.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["0", "1", "2", "3"]);
+    }
+}
@@ -0,0 +1,97 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::cancellation_token::CancellationToken;
+use crate::command::Command;
+use crate::event::{EventDestination, EventPayload};
+use crate::state::Effect;
+
+pub trait Middleware<S, M> {
+    fn on_message(&mut self, _message: &M) {}
+
+    fn before_update(&mut self, message: M, _state: &S) -> Option<M> {
+        Some(message)
+    }
+
+    fn after_update(&mut self, _state: &S, _effect: &Effect) {}
+
+    fn on_event(&mut self, _destination: &EventDestination, _payload: &EventPayload) {}
+
+    fn on_effect(&mut self, _effect: &Effect) {}
+
+    fn on_command(
+        &mut self,
+        _command: &Command<M>,
+        _cancellation_token: Option<&CancellationToken>,
+    ) {
+    }
+
+    fn flush(&mut self, _state: &S) {}
+}
+
+impl<S, M, T> Middleware<S, M> for Box<T>
+where
+    T: Middleware<S, M> + ?Sized,
+{
+    fn on_message(&mut self, message: &M) {
+        (**self).on_message(message)
+    }
+
+    fn before_update(&mut self, message: M, state: &S) -> Option<M> {
+        (**self).before_update(message, state)
+    }
+
+    fn after_update(&mut self, state: &S, effect: &Effect) {
+        (**self).after_update(state, effect)
+    }
+
+    fn on_event(&mut self, destination: &EventDestination, payload: &EventPayload) {
+        (**self).on_event(destination, payload)
+    }
+
+    fn on_effect(&mut self, effect: &Effect) {
+        (**self).on_effect(effect)
+    }
+
+    fn on_command(&mut self, command: &Command<M>, cancellation_token: Option<&CancellationToken>) {
+        (**self).on_command(command, cancellation_token)
+    }
+
+    fn flush(&mut self, state: &S) {
+        (**self).flush(state)
+    }
+}
+
+// Lets the caller keep a handle to a middleware after handing it to a render loop.
+impl<S, M, T> Middleware<S, M> for Rc<RefCell<T>>
+where
+    T: Middleware<S, M> + ?Sized,
+{
+    fn on_message(&mut self, message: &M) {
+        self.borrow_mut().on_message(message)
+    }
+
+    fn before_update(&mut self, message: M, state: &S) -> Option<M> {
+        self.borrow_mut().before_update(message, state)
+    }
+
+    fn after_update(&mut self, state: &S, effect: &Effect) {
+        self.borrow_mut().after_update(state, effect)
+    }
+
+    fn on_event(&mut self, destination: &EventDestination, payload: &EventPayload) {
+        self.borrow_mut().on_event(destination, payload)
+    }
+
+    fn on_effect(&mut self, effect: &Effect) {
+        self.borrow_mut().on_effect(effect)
+    }
+
+    fn on_command(&mut self, command: &Command<M>, cancellation_token: Option<&CancellationToken>) {
+        self.borrow_mut().on_command(command, cancellation_token)
+    }
+
+    fn flush(&mut self, state: &S) {
+        self.borrow_mut().flush(state)
+    }
+}
@@ -0,0 +1,285 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::middleware::Middleware;
+use crate::state::Effect;
+
+const VERSION_KEY: &str = "version";
+const STATE_KEY: &str = "state";
+
+pub trait Persistent: Serialize + DeserializeOwned {
+    const VERSION: u32;
+
+    fn migrate(version: u32, _data: &mut Value) -> Result<(), PersistenceError> {
+        Err(PersistenceError::UnsupportedVersion(version))
+    }
+}
+
+#[derive(Debug)]
+pub struct Persistence {
+    path: PathBuf,
+    debounce: Duration,
+    last_saved_at: Option<Instant>,
+    is_dirty: bool,
+}
+
+impl Persistence {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            debounce: Duration::from_secs(1),
+            last_saved_at: None,
+            is_dirty: false,
+        }
+    }
+
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn restore<S: Persistent>(&self) -> Result<Option<S>, PersistenceError> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let snapshot = serde_json::from_str(&contents)?;
+        decode(snapshot).map(Some)
+    }
+
+    pub fn save<S: Persistent>(&mut self, state: &S) -> Result<(), PersistenceError> {
+        let snapshot = encode(state)?;
+        let contents = serde_json::to_string(&snapshot)?;
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temporary_path = self.path.with_extension("tmp");
+        fs::write(&temporary_path, contents)?;
+        fs::rename(&temporary_path, &self.path)?;
+        self.last_saved_at = Some(Instant::now());
+        self.is_dirty = false;
+        Ok(())
+    }
+
+    pub fn flush<S: Persistent>(&mut self, state: &S) -> Result<(), PersistenceError> {
+        if self.is_dirty {
+            self.save(state)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn should_save(&self) -> bool {
+        self.is_dirty
+            && self
+                .last_saved_at
+                .is_none_or(|last_saved_at| last_saved_at.elapsed() >= self.debounce)
+    }
+}
+
+impl<S, M> Middleware<S, M> for Persistence
+where
+    S: Persistent,
+{
+    fn after_update(&mut self, state: &S, effect: &Effect) {
+        if !matches!(effect, Effect::Nop) {
+            self.is_dirty = true;
+        }
+        if self.should_save() {
+            // Failures are retried on the next update since the state stays dirty.
+            let _ = self.save(state);
+        }
+    }
+
+    // Writes the updates that the debounce held back, e.g. when the application exits.
+    fn flush(&mut self, state: &S) {
+        let _ = Persistence::flush(self, state);
+    }
+}
+
+pub fn encode<S: Persistent>(state: &S) -> Result<Value, PersistenceError> {
+    let mut snapshot = serde_json::Map::new();
+    snapshot.insert(VERSION_KEY.to_owned(), S::VERSION.into());
+    snapshot.insert(STATE_KEY.to_owned(), serde_json::to_value(state)?);
+    Ok(Value::Object(snapshot))
+}
+
+pub fn decode<S: Persistent>(snapshot: Value) -> Result<S, PersistenceError> {
+    let Value::Object(mut snapshot) = snapshot else {
+        return Err(PersistenceError::Malformed);
+    };
+    let mut version = snapshot
+        .get(VERSION_KEY)
+        .and_then(Value::as_u64)
+        .and_then(|version| u32::try_from(version).ok())
+        .ok_or(PersistenceError::Malformed)?;
+    let mut data = snapshot
+        .remove(STATE_KEY)
+        .ok_or(PersistenceError::Malformed)?;
+    if version > S::VERSION {
+        return Err(PersistenceError::UnsupportedVersion(version));
+    }
+    while version < S::VERSION {
+        S::migrate(version, &mut data)?;
+        version += 1;
+    }
+    Ok(serde_json::from_value(data)?)
+}
+
+#[derive(Debug)]
+pub enum PersistenceError {
+    Io(io::Error),
+    Serde(serde_json::Error),
+    Malformed,
+    UnsupportedVersion(u32),
+    Migration(String),
+}
+
+impl fmt::Display for PersistenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => error.fmt(f),
+            Self::Serde(error) => error.fmt(f),
+            Self::Malformed => f.write_str("Malformed snapshot"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Unsupported snapshot version: {}", version)
+            }
+            Self::Migration(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Error for PersistenceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Serde(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PersistenceError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for PersistenceError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serde(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::atom_collection::AtomVec;
+    use crate::state::Atom;
+
+    #[derive(Debug, Default, Deserialize, Serialize)]
+    struct TestState {
+        filter: Atom<String>,
+        tabs: AtomVec<String>,
+    }
+
+    impl Persistent for TestState {
+        const VERSION: u32 = 2;
+
+        fn migrate(version: u32, data: &mut Value) -> Result<(), PersistenceError> {
+            match version {
+                0 => {
+                    let tab = data["tab"].take();
+                    data["tabs"] = Value::Array(vec![tab]);
+                    Ok(())
+                }
+                1 => {
+                    data.as_object_mut()
+                        .ok_or(PersistenceError::Malformed)?
+                        .entry("filter")
+                        .or_insert_with(|| Value::String(String::new()));
+                    Ok(())
+                }
+                _ => Err(PersistenceError::UnsupportedVersion(version)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_persistence() {
+        let state = TestState {
+            filter: Atom::new("done".to_owned()),
+            tabs: ["foo".to_owned()].into_iter().collect(),
+        };
+        let snapshot = encode(&state).unwrap();
+        assert_eq!(
+            snapshot,
+            serde_json::json!({
+                "version": 2,
+                "state": { "filter": "done", "tabs": ["foo"] }
+            })
+        );
+
+        let restored: TestState = decode(snapshot).unwrap();
+        assert_eq!(restored.filter.get(), "done");
+        assert_eq!(restored.tabs.iter().collect::<Vec<_>>(), ["foo"]);
+
+        let restored: TestState = decode(serde_json::json!({
+            "version": 0,
+            "state": { "tab": "bar" }
+        }))
+        .unwrap();
+        assert_eq!(restored.filter.get(), "");
+        assert_eq!(restored.tabs.iter().collect::<Vec<_>>(), ["bar"]);
+
+        assert!(matches!(
+            decode::<TestState>(serde_json::json!({ "version": 3, "state": {} })),
+            Err(PersistenceError::UnsupportedVersion(3))
+        ));
+
+        let path =
+            std::env::temp_dir().join(format!("yuiui-persistence-{}.json", std::process::id()));
+        let mut persistence = Persistence::new(&path);
+        assert!(persistence.restore::<TestState>().unwrap().is_none());
+        persistence.save(&state).unwrap();
+        let restored = persistence.restore::<TestState>().unwrap().unwrap();
+        assert_eq!(restored.filter.get(), "done");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_flush_debounced_updates() {
+        let path = std::env::temp_dir().join(format!(
+            "yuiui-persistence-flush-{}.json",
+            std::process::id()
+        ));
+        let mut persistence = Persistence::new(&path).with_debounce(Duration::from_secs(3600));
+        let mut state = TestState::default();
+
+        let effect = state.filter.set("foo".to_owned());
+        Middleware::<TestState, ()>::after_update(&mut persistence, &state, &effect);
+        let effect = state.filter.set("bar".to_owned());
+        Middleware::<TestState, ()>::after_update(&mut persistence, &state, &effect);
+        let restored = persistence.restore::<TestState>().unwrap().unwrap();
+        assert_eq!(restored.filter.get(), "foo");
+
+        Middleware::<TestState, ()>::flush(&mut persistence, &state);
+        let restored = persistence.restore::<TestState>().unwrap().unwrap();
+        assert_eq!(restored.filter.get(), "bar");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,32 @@
+use std::ops::{Index, IndexMut};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Priority {
+    UserInput,
+    #[default]
+    Normal,
+    Background,
+}
+
+impl Priority {
+    pub const ALL: [Priority; 3] = [Self::UserInput, Self::Normal, Self::Background];
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Lanes<T> {
+    lanes: [T; 3],
+}
+
+impl<T> Index<Priority> for Lanes<T> {
+    type Output = T;
+
+    fn index(&self, priority: Priority) -> &Self::Output {
+        &self.lanes[priority as usize]
+    }
+}
+
+impl<T> IndexMut<Priority> for Lanes<T> {
+    fn index_mut(&mut self, priority: Priority) -> &mut Self::Output {
+        &mut self.lanes[priority as usize]
+    }
+}
@@ -0,0 +1,211 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+use crate::event::Lifecycle;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Phase {
+    Message,
+    Event,
+    Update,
+    Commit,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Message => "message",
+            Self::Event => "event",
+            Self::Update => "update",
+            Self::Commit => "commit",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProfileReport {
+    pub duration: Duration,
+    pub messages_processed: usize,
+    pub events_processed: usize,
+    pub nodes_visited: usize,
+    pub nodes_changed: usize,
+    pub phases: BTreeMap<Phase, Duration>,
+    pub components: BTreeMap<&'static str, ComponentProfile>,
+    pub lifecycles: BTreeMap<&'static str, LifecycleProfile>,
+    pub stacks: BTreeMap<String, Duration>,
+}
+
+impl ProfileReport {
+    pub fn to_folded_stacks(&self) -> String {
+        let mut output = String::new();
+        for (stack, duration) in &self.stacks {
+            writeln!(output, "{} {}", stack, duration.as_micros()).unwrap();
+        }
+        output
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ComponentProfile {
+    pub renders: usize,
+    pub duration: Duration,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LifecycleProfile {
+    pub mounts: usize,
+    pub remounts: usize,
+    pub updates: usize,
+    pub unmounts: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct Profiler {
+    current: RefCell<Option<(Instant, ProfileReport)>>,
+    frames: RefCell<Vec<Frame>>,
+    reports: RefCell<Vec<ProfileReport>>,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn take_reports(&self) -> Vec<ProfileReport> {
+        self.reports.take()
+    }
+
+    pub fn to_folded_stacks(&self) -> String {
+        let mut stacks: BTreeMap<String, Duration> = BTreeMap::new();
+        for report in self.reports.borrow().iter() {
+            for (stack, duration) in &report.stacks {
+                *stacks.entry(stack.clone()).or_default() += *duration;
+            }
+        }
+        ProfileReport {
+            stacks,
+            ..Default::default()
+        }
+        .to_folded_stacks()
+    }
+
+    pub(crate) fn begin_run(&self) {
+        *self.current.borrow_mut() = Some((Instant::now(), ProfileReport::default()));
+        self.frames.borrow_mut().clear();
+    }
+
+    pub(crate) fn end_run(&self) {
+        if let Some((started_at, mut report)) = self.current.take() {
+            report.duration = started_at.elapsed();
+            self.reports.borrow_mut().push(report);
+        }
+    }
+
+    pub(crate) fn enter_phase(&self, phase: Phase) -> ProfileSpan<'_> {
+        match phase {
+            Phase::Message => self.record(|report| report.messages_processed += 1),
+            Phase::Event => self.record(|report| report.events_processed += 1),
+            Phase::Update | Phase::Commit => {}
+        }
+        self.enter(phase.name(), FrameKind::Phase(phase))
+    }
+
+    pub(crate) fn enter_component(&self, name: &'static str) -> ProfileSpan<'_> {
+        self.enter(name, FrameKind::Component)
+    }
+
+    pub(crate) fn record_visit(&self, has_changed: bool) {
+        self.record(|report| {
+            report.nodes_visited += 1;
+            if has_changed {
+                report.nodes_changed += 1;
+            }
+        });
+    }
+
+    pub(crate) fn record_lifecycle<T>(&self, name: &'static str, lifecycle: &Lifecycle<T>) {
+        self.record(|report| {
+            let profile = report.lifecycles.entry(name).or_default();
+            match lifecycle {
+                Lifecycle::Mount => profile.mounts += 1,
+                Lifecycle::Remount => profile.remounts += 1,
+                Lifecycle::Update(_) => profile.updates += 1,
+                Lifecycle::Unmount => profile.unmounts += 1,
+            }
+        });
+    }
+
+    fn enter(&self, name: &'static str, kind: FrameKind) -> ProfileSpan<'_> {
+        self.frames.borrow_mut().push(Frame {
+            name,
+            kind,
+            started_at: Instant::now(),
+            children_duration: Duration::ZERO,
+        });
+        ProfileSpan { profiler: self }
+    }
+
+    fn exit(&self) {
+        let mut frames = self.frames.borrow_mut();
+        let Some(frame) = frames.pop() else {
+            return;
+        };
+        let duration = frame.started_at.elapsed();
+        let mut stack = String::new();
+        for parent in frames.iter() {
+            stack.push_str(&parent.name.replace(';', ","));
+            stack.push(';');
+        }
+        stack.push_str(&frame.name.replace(';', ","));
+        if let Some(parent) = frames.last_mut() {
+            parent.children_duration += duration;
+        }
+        drop(frames);
+        self.record(|report| {
+            *report.stacks.entry(stack).or_default() +=
+                duration.saturating_sub(frame.children_duration);
+            match frame.kind {
+                FrameKind::Phase(phase) => {
+                    *report.phases.entry(phase).or_default() += duration;
+                }
+                FrameKind::Component => {
+                    let profile = report.components.entry(frame.name).or_default();
+                    profile.renders += 1;
+                    profile.duration += duration;
+                }
+            }
+        });
+    }
+
+    fn record(&self, f: impl FnOnce(&mut ProfileReport)) {
+        if let Some((_, report)) = self.current.borrow_mut().as_mut() {
+            f(report);
+        }
+    }
+}
+
+pub(crate) struct ProfileSpan<'a> {
+    profiler: &'a Profiler,
+}
+
+impl<'a> Drop for ProfileSpan<'a> {
+    fn drop(&mut self) {
+        self.profiler.exit();
+    }
+}
+
+#[derive(Debug)]
+struct Frame {
+    name: &'static str,
+    kind: FrameKind,
+    started_at: Instant,
+    children_duration: Duration,
+}
+
+#[derive(Debug)]
+enum FrameKind {
+    Phase(Phase),
+    Component,
+}
@@ -0,0 +1,254 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::{cmp, fmt, mem};
+
+use crate::id::{Id, IdPath, IdTree, Level, NodePath};
+
+#[derive(Default)]
+pub(crate) struct ProviderRegistry {
+    entries: HashMap<Id, Vec<ProviderEntry>>,
+    pending_updates: RefCell<Vec<NodePath>>,
+}
+
+impl ProviderRegistry {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn provide<T>(&mut self, id: Id, value: T)
+    where
+        T: PartialEq + 'static,
+    {
+        let entries = self.entries.entry(id).or_default();
+        if let Some(entry) = entries.iter_mut().find(|entry| entry.value.is::<T>()) {
+            let old_value = entry.value.downcast_ref::<T>().unwrap();
+            if old_value != &value {
+                entry.value = Rc::new(value);
+                self.pending_updates
+                    .get_mut()
+                    .extend(mem::take(entry.subscribers.get_mut()));
+            }
+        } else {
+            entries.push(ProviderEntry {
+                value: Rc::new(value),
+                subscribers: RefCell::new(Vec::new()),
+            });
+        }
+    }
+
+    pub(crate) fn lookup<T>(&self, id_path: &IdPath, level: Level) -> Option<Rc<T>>
+    where
+        T: 'static,
+    {
+        let ancestors = id_path.iter().rev().copied().chain([Id::ROOT]);
+        for id in ancestors {
+            let entry = self
+                .entries
+                .get(&id)
+                .and_then(|entries| entries.iter().find(|entry| entry.value.is::<T>()));
+            if let Some(entry) = entry {
+                // The consumer is rendered right now, so it no longer needs a pending update.
+                self.pending_updates
+                    .borrow_mut()
+                    .retain(|subscriber| subscriber.id_path != id_path || subscriber.level > level);
+                entry.subscribe(id_path, level);
+                return Some(entry.value.clone().downcast().unwrap());
+            }
+        }
+        None
+    }
+
+    pub(crate) fn remove(&mut self, id: Id) {
+        self.entries.remove(&id);
+    }
+
+    pub(crate) fn unsubscribe(&mut self, id_path: &IdPath) {
+        let ancestors = id_path.iter().rev().copied().chain([Id::ROOT]);
+        for id in ancestors {
+            if let Some(entries) = self.entries.get_mut(&id) {
+                for entry in entries {
+                    entry
+                        .subscribers
+                        .get_mut()
+                        .retain(|subscriber| subscriber.id_path != id_path);
+                }
+            }
+        }
+        self.pending_updates
+            .get_mut()
+            .retain(|subscriber| subscriber.id_path != id_path);
+    }
+
+    pub(crate) fn take_pending_updates(&mut self) -> Vec<NodePath> {
+        mem::take(self.pending_updates.get_mut())
+    }
+
+    pub(crate) fn take_pending_updates_within(&mut self, id_path: &IdPath) -> IdTree<Level> {
+        let mut id_tree = IdTree::new();
+        self.pending_updates.get_mut().retain(|subscriber| {
+            match subscriber.id_path.strip_prefix(id_path) {
+                Some(tail) if !tail.is_empty() => {
+                    id_tree.insert_or_update(tail, subscriber.level, cmp::max);
+                    false
+                }
+                _ => true,
+            }
+        });
+        id_tree
+    }
+}
+
+impl fmt::Debug for ProviderRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProviderRegistry")
+            .field("entries", &self.entries.keys())
+            .field("pending_updates", &self.pending_updates)
+            .finish()
+    }
+}
+
+struct ProviderEntry {
+    value: Rc<dyn Any>,
+    subscribers: RefCell<Vec<NodePath>>,
+}
+
+impl ProviderEntry {
+    fn subscribe(&self, id_path: &IdPath, level: Level) {
+        let mut subscribers = self.subscribers.borrow_mut();
+        for subscriber in subscribers.iter_mut() {
+            if subscriber.id_path == id_path {
+                if subscriber.level < level {
+                    subscriber.level = level;
+                }
+                return;
+            }
+        }
+        subscribers.push(NodePath {
+            id_path: id_path.to_vec(),
+            level,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::HigherOrderComponent;
+    use crate::context::RenderContext;
+    use crate::deadline::StepBudget;
+    use crate::element::{Element, MemoizeElement, ProviderElement, ViewElement};
+    use crate::render_loop::{RenderFlow, RenderLoop};
+    use crate::test_utils::{Container, Label, NullRuntime, TestState};
+    use crate::view::View;
+
+    type Message = &'static str;
+
+    type RenderFn = fn(
+        &(),
+        &mut RenderContext<TestState<Message>>,
+    ) -> ViewElement<Label, TestState<Message>, Message, ()>;
+
+    type AppContainer = Container<(MemoizeElement<RenderFn, ()>, MemoizeElement<RenderFn, ()>)>;
+
+    type AppFn = fn(
+        &(),
+        &mut RenderContext<TestState<Message>>,
+    ) -> ProviderElement<
+        ViewElement<AppContainer, TestState<Message>, Message, ()>,
+        &'static str,
+    >;
+
+    type RootContainer = Container<(MemoizeElement<AppFn, ()>,)>;
+
+    fn consumer(
+        _props: &(),
+        context: &mut RenderContext<TestState<Message>>,
+    ) -> ViewElement<Label, TestState<Message>, Message, ()> {
+        let theme = context.use_context::<&'static str>().unwrap();
+        Label(format!("consumer {}", theme)).el(())
+    }
+
+    fn other(
+        _props: &(),
+        _context: &mut RenderContext<TestState<Message>>,
+    ) -> ViewElement<Label, TestState<Message>, Message, ()> {
+        Label::new("other").el(())
+    }
+
+    fn app(
+        _props: &(),
+        context: &mut RenderContext<TestState<Message>>,
+    ) -> ProviderElement<ViewElement<AppContainer, TestState<Message>, Message, ()>, &'static str>
+    {
+        let theme = *context.use_atom(|state| &state.value);
+        Container::new()
+            .el((
+                (consumer as RenderFn).memoize(()),
+                (other as RenderFn).memoize(()),
+            ))
+            .provide(theme)
+    }
+
+    #[test]
+    fn test_use_context() {
+        let mut state = TestState::new("light");
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec!["mount consumer light", "mount other"]
+        );
+
+        render_loop.push_message("dark");
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update consumer dark"]);
+
+        render_loop.push_message("dark");
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert!(state.log.take().is_empty());
+    }
+
+    #[test]
+    fn test_update_consumers_in_same_pass() {
+        fn root(
+            _props: &(),
+            _context: &mut RenderContext<TestState<Message>>,
+        ) -> ViewElement<RootContainer, TestState<Message>, Message, ()> {
+            Container::new().el(((app as AppFn).memoize(()),))
+        }
+
+        let mut state = TestState::new("light");
+        let mut render_loop = RenderLoop::create(root.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        state.log.take();
+
+        // The budget expires right after the first update, i.e. the one of the provider.
+        render_loop.push_message("dark");
+        let render_flow = render_loop.run_until(&mut state, &(), &NullRuntime, &StepBudget::new(1));
+        assert_eq!(render_flow, RenderFlow::Suspend);
+        assert_eq!(render_loop.pending_work().nodes_to_update, 0);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update consumer dark"]);
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let consumer = [Id::ROOT];
+        let mut providers = ProviderRegistry::new();
+        providers.provide(Id::ROOT, "light");
+        providers.lookup::<&'static str>(&consumer, 1);
+
+        providers.unsubscribe(&consumer);
+        providers.provide(Id::ROOT, "dark");
+        assert!(providers.take_pending_updates().is_empty());
+
+        providers.lookup::<&'static str>(&consumer, 1);
+        providers.provide(Id::ROOT, "light");
+        assert_eq!(providers.take_pending_updates().len(), 1);
+    }
+}
@@ -0,0 +1,635 @@
+use futures::executor;
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::marker::PhantomData;
+use std::mem;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::cancellation_token::CancellationToken;
+use crate::command::{Command, CommandRuntime};
+use crate::element::Element;
+use crate::event::{EventDestination, EventPayload};
+use crate::middleware::Middleware;
+use crate::render_loop::RenderLoop;
+use crate::state::{Effect, State};
+
+type PayloadSerializer = fn(&(dyn Any + Send)) -> Result<Value, serde_json::Error>;
+
+type PayloadDeserializer = fn(Value) -> Result<EventPayload, serde_json::Error>;
+
+#[derive(Clone, Debug, Default)]
+pub struct PayloadRegistry {
+    serializers: HashMap<TypeId, (String, PayloadSerializer)>,
+    deserializers: HashMap<String, PayloadDeserializer>,
+}
+
+impl PayloadRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register<T>(&mut self, name: impl Into<String>)
+    where
+        T: Serialize + DeserializeOwned + Send + 'static,
+    {
+        let name = name.into();
+        self.serializers
+            .insert(TypeId::of::<T>(), (name.clone(), serialize_payload::<T>));
+        self.deserializers.insert(name, deserialize_payload::<T>);
+    }
+
+    fn serialize(&self, payload: &EventPayload) -> Result<(String, Value), RecordingError> {
+        let type_id = (**payload).type_id();
+        let (name, serializer) = self
+            .serializers
+            .get(&type_id)
+            .ok_or(RecordingError::UnregisteredPayload(type_id))?;
+        let value = serializer(payload.as_ref())?;
+        Ok((name.clone(), value))
+    }
+
+    fn deserialize(&self, name: &str, value: Value) -> Result<EventPayload, RecordingError> {
+        let deserializer = self
+            .deserializers
+            .get(name)
+            .ok_or_else(|| RecordingError::UnknownPayload(name.to_owned()))?;
+        Ok(deserializer(value)?)
+    }
+}
+
+fn serialize_payload<T: Serialize + 'static>(
+    payload: &(dyn Any + Send),
+) -> Result<Value, serde_json::Error> {
+    let payload = payload
+        .downcast_ref::<T>()
+        .expect("The payload type must match the registered type");
+    serde_json::to_value(payload)
+}
+
+fn deserialize_payload<T: DeserializeOwned + Send + 'static>(
+    value: Value,
+) -> Result<EventPayload, serde_json::Error> {
+    let payload: T = serde_json::from_value(value)?;
+    Ok(Box::new(payload))
+}
+
+// Messages are recorded once every middleware before the recorder has let them through, since a
+// replay runs without middlewares. Add the recorder last so that it sees the messages as applied.
+#[derive(Debug)]
+pub struct Recorder<M> {
+    writer: BufWriter<File>,
+    payloads: PayloadRegistry,
+    started_at: Instant,
+    pending_message: Option<(u64, Value)>,
+    errors: Vec<RecordingError>,
+    _phantom: PhantomData<M>,
+}
+
+impl<M> Recorder<M>
+where
+    M: Serialize,
+{
+    pub fn create<S: Serialize>(
+        path: impl AsRef<Path>,
+        state: &S,
+        payloads: PayloadRegistry,
+    ) -> Result<Self, RecordingError> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, &Header { state })?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(Self {
+            writer,
+            payloads,
+            started_at: Instant::now(),
+            pending_message: None,
+            errors: Vec::new(),
+            _phantom: PhantomData,
+        })
+    }
+
+    pub fn take_errors(&mut self) -> Vec<RecordingError> {
+        mem::take(&mut self.errors)
+    }
+
+    fn write_record(&mut self, record: RawRecord<Value, &EventDestination, &Effect>) {
+        // Recording is best-effort so that a full disk does not bring down the application.
+        let result = serde_json::to_writer(&mut self.writer, &record)
+            .map_err(RecordingError::from)
+            .and_then(|_| {
+                self.writer.write_all(b"\n")?;
+                self.writer.flush()?;
+                Ok(())
+            });
+        if let Err(error) = result {
+            self.errors.push(error);
+        }
+    }
+
+    fn elapsed(&self) -> u64 {
+        self.started_at.elapsed().as_micros() as u64
+    }
+}
+
+impl<S, M> Middleware<S, M> for Recorder<M>
+where
+    M: Serialize,
+{
+    fn before_update(&mut self, message: M, _state: &S) -> Option<M> {
+        let time = self.elapsed();
+        match serde_json::to_value(&message) {
+            Ok(value) => self.pending_message = Some((time, value)),
+            Err(error) => self.errors.push(error.into()),
+        }
+        Some(message)
+    }
+
+    fn after_update(&mut self, _state: &S, _effect: &Effect) {
+        if let Some((time, message)) = self.pending_message.take() {
+            self.write_record(RawRecord::Message { time, message });
+        }
+    }
+
+    fn on_event(&mut self, destination: &EventDestination, payload: &EventPayload) {
+        let time = self.elapsed();
+        match self.payloads.serialize(payload) {
+            Ok((payload_type, payload)) => self.write_record(RawRecord::Event {
+                time,
+                destination,
+                payload_type,
+                payload,
+            }),
+            Err(error) => {
+                // The placeholder keeps the position of the event that cannot be replayed.
+                self.write_record(RawRecord::OpaqueEvent { time, destination });
+                self.errors.push(error);
+            }
+        }
+    }
+
+    fn on_effect(&mut self, effect: &Effect) {
+        let time = self.elapsed();
+        self.write_record(RawRecord::Effect { time, effect });
+    }
+}
+
+#[derive(Debug)]
+pub enum SessionRecord<M> {
+    Message {
+        time: Duration,
+        message: M,
+    },
+    Event {
+        time: Duration,
+        destination: EventDestination,
+        payload: EventPayload,
+    },
+    OpaqueEvent {
+        time: Duration,
+        destination: EventDestination,
+    },
+    Effect {
+        time: Duration,
+        effect: Effect,
+    },
+}
+
+impl<M> SessionRecord<M> {
+    pub fn time(&self) -> Duration {
+        match self {
+            Self::Message { time, .. }
+            | Self::Event { time, .. }
+            | Self::OpaqueEvent { time, .. }
+            | Self::Effect { time, .. } => *time,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Session<S, M> {
+    state: S,
+    records: Vec<SessionRecord<M>>,
+}
+
+impl<S, M> Session<S, M>
+where
+    S: DeserializeOwned,
+    M: DeserializeOwned,
+{
+    pub fn load(
+        path: impl AsRef<Path>,
+        payloads: &PayloadRegistry,
+    ) -> Result<Self, RecordingError> {
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let header: Header<S> =
+            serde_json::from_str(lines.next().ok_or(RecordingError::Malformed)?)?;
+        let mut records = Vec::new();
+        for line in lines {
+            let record = match serde_json::from_str(line)? {
+                RawRecord::Message { time, message } => SessionRecord::Message {
+                    time: Duration::from_micros(time),
+                    message,
+                },
+                RawRecord::Event {
+                    time,
+                    destination,
+                    payload_type,
+                    payload,
+                } => SessionRecord::Event {
+                    time: Duration::from_micros(time),
+                    destination,
+                    payload: payloads.deserialize(&payload_type, payload)?,
+                },
+                RawRecord::OpaqueEvent { time, destination } => SessionRecord::OpaqueEvent {
+                    time: Duration::from_micros(time),
+                    destination,
+                },
+                RawRecord::Effect { time, effect } => SessionRecord::Effect {
+                    time: Duration::from_micros(time),
+                    effect,
+                },
+            };
+            records.push(record);
+        }
+        Ok(Self {
+            state: header.state,
+            records,
+        })
+    }
+}
+
+impl<S, M> Session<S, M> {
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+
+    pub fn records(&self) -> &[SessionRecord<M>] {
+        &self.records
+    }
+
+    pub fn into_parts(self) -> (S, Vec<SessionRecord<M>>) {
+        (self.state, self.records)
+    }
+
+    pub fn replay<Element, E>(
+        self,
+        element: Element,
+        entry_point: &E,
+    ) -> (RenderLoop<Element, S, M, E>, S)
+    where
+        Element: self::Element<S, M, E>,
+        S: State<Message = M>,
+    {
+        let Self { mut state, records } = self;
+        // Messages from commands are part of the recording, so only effects are resolved again.
+        let command_runtime = ReplayRuntime::default();
+        let mut render_loop = RenderLoop::create(element, &state);
+        render_loop.run_forever(&mut state, entry_point, &command_runtime);
+        for record in records {
+            match record {
+                SessionRecord::Message { message, .. } => render_loop.push_message(message),
+                SessionRecord::Event {
+                    destination,
+                    payload,
+                    ..
+                } => render_loop.push_event(destination, payload),
+                // The payload was never recorded, so the event cannot be dispatched again.
+                SessionRecord::OpaqueEvent { .. } => continue,
+                SessionRecord::Effect { effect, .. } => {
+                    command_runtime.resolve(&effect);
+                    render_loop.push_effect(effect);
+                }
+            }
+            render_loop.run_forever(&mut state, entry_point, &command_runtime);
+        }
+        (render_loop, state)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct Header<S> {
+    state: S,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+enum RawRecord<M, D, F> {
+    Message {
+        time: u64,
+        message: M,
+    },
+    Event {
+        time: u64,
+        destination: D,
+        payload_type: String,
+        payload: Value,
+    },
+    OpaqueEvent {
+        time: u64,
+        destination: D,
+    },
+    Effect {
+        time: u64,
+        effect: F,
+    },
+}
+
+#[derive(Default)]
+struct ReplayRuntime {
+    commands: RefCell<VecDeque<Command<Effect>>>,
+    effects: RefCell<Vec<Effect>>,
+}
+
+impl ReplayRuntime {
+    // Runs the spawned effect commands in order until one of them yields the recorded effect, so
+    // that its side effects (e.g. filling a suspense resource) happen before the effect is pushed.
+    fn resolve(&self, effect: &Effect) {
+        let mut effects = self.effects.borrow_mut();
+        if let Some(index) = effects.iter().position(|resolved| resolved == effect) {
+            effects.remove(index);
+            return;
+        }
+        let mut commands = self.commands.borrow_mut();
+        for _ in 0..commands.len() {
+            let resolved = match commands.pop_front().unwrap() {
+                Command::Future(future) => executor::block_on(future),
+                Command::Stream(mut stream) => match executor::block_on(stream.next()) {
+                    Some(resolved) => {
+                        commands.push_back(Command::Stream(stream));
+                        resolved
+                    }
+                    None => continue,
+                },
+                Command::Timeout(_, callback) => callback(),
+                Command::Interval(period, mut callback) => {
+                    let resolved = callback();
+                    commands.push_back(Command::Interval(period, callback));
+                    resolved
+                }
+            };
+            if resolved == *effect {
+                return;
+            }
+            effects.push(resolved);
+        }
+    }
+}
+
+impl<M> CommandRuntime<M> for ReplayRuntime {
+    fn spawn_command(&self, _command: Command<M>, _cancellation_token: Option<CancellationToken>) {}
+
+    fn spawn_effect(
+        &self,
+        command: Command<Effect>,
+        _cancellation_token: Option<CancellationToken>,
+    ) {
+        self.commands.borrow_mut().push_back(command);
+    }
+}
+
+#[derive(Debug)]
+pub enum RecordingError {
+    Io(io::Error),
+    Serde(serde_json::Error),
+    Malformed,
+    UnregisteredPayload(TypeId),
+    UnknownPayload(String),
+}
+
+impl fmt::Display for RecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => error.fmt(f),
+            Self::Serde(error) => error.fmt(f),
+            Self::Malformed => f.write_str("Malformed session"),
+            Self::UnregisteredPayload(type_id) => {
+                write!(f, "Unregistered payload type: {:?}", type_id)
+            }
+            Self::UnknownPayload(name) => write!(f, "Unknown payload type: {}", name),
+        }
+    }
+}
+
+impl Error for RecordingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Serde(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for RecordingError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for RecordingError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serde(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::future;
+
+    use super::*;
+    use crate::component::HigherOrderComponent;
+    use crate::context::{CommitContext, RenderContext};
+    use crate::element::ElementSeq;
+    use crate::error::RenderError;
+    use crate::event::EventTarget;
+    use crate::storages::Suspense;
+    use crate::test_utils::{Container, Label, TestState};
+    use crate::view::View;
+
+    struct Button;
+
+    impl View<TestState<i64>, i64, ()> for Button {
+        type Children = ();
+
+        type State = ();
+
+        fn event(
+            &self,
+            value: <Self as EventTarget>::Event,
+            _view_state: &mut Self::State,
+            _children: &mut <Self::Children as ElementSeq<TestState<i64>, i64, ()>>::Storage,
+            context: &mut CommitContext<TestState<i64>, i64, ()>,
+        ) {
+            context.dispatch(*value);
+        }
+
+        fn build(
+            &self,
+            _children: &mut <Self::Children as ElementSeq<TestState<i64>, i64, ()>>::Storage,
+            _context: &mut CommitContext<TestState<i64>, i64, ()>,
+        ) -> Self::State {
+        }
+    }
+
+    impl<'event> EventTarget<'event> for Button {
+        type Event = &'event i64;
+    }
+
+    #[test]
+    fn test_recording() {
+        let path =
+            std::env::temp_dir().join(format!("yuiui-recording-{}.jsonl", std::process::id()));
+        let mut payloads = PayloadRegistry::new();
+        payloads.register::<i64>("value");
+
+        let state = TestState::new(10);
+        let mut recorder = Recorder::create(&path, &state, payloads.clone()).unwrap();
+        let destination = EventDestination::Unicast(Vec::new());
+        Middleware::<TestState<i64>, i64>::before_update(&mut recorder, 1, &state);
+        Middleware::<TestState<i64>, i64>::after_update(&mut recorder, &state, &Effect::Nop);
+        // A message dropped by a later middleware is never applied, so it is not recorded.
+        Middleware::<TestState<i64>, i64>::before_update(&mut recorder, 5, &state);
+        Middleware::<TestState<i64>, i64>::before_update(&mut recorder, 3, &state);
+        Middleware::<TestState<i64>, i64>::after_update(&mut recorder, &state, &Effect::Nop);
+        Middleware::<TestState<i64>, i64>::on_event(
+            &mut recorder,
+            &destination,
+            &(Box::new(2i64) as EventPayload),
+        );
+        Middleware::<TestState<i64>, i64>::on_event(
+            &mut recorder,
+            &destination,
+            &(Box::new("skip") as EventPayload),
+        );
+        assert!(matches!(
+            recorder.take_errors().as_slice(),
+            [RecordingError::UnregisteredPayload(type_id)] if *type_id == TypeId::of::<&str>()
+        ));
+        drop(recorder);
+
+        let session: Session<TestState<i64>, i64> = Session::load(&path, &payloads).unwrap();
+        assert_eq!(*session.state().value.get(), 10);
+        assert_eq!(session.records().len(), 4);
+        assert!(matches!(
+            session.records()[3],
+            SessionRecord::OpaqueEvent { .. }
+        ));
+        assert!(session
+            .records()
+            .windows(2)
+            .all(|records| records[0].time() <= records[1].time()));
+
+        let (_, state) = session.replay(Button.el(()), &());
+        assert_eq!(*state.value.get(), 2);
+
+        assert!(matches!(
+            Session::<TestState<i64>, i64>::load(&path, &PayloadRegistry::new()),
+            Err(RecordingError::UnknownPayload(name)) if name == "value"
+        ));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[derive(Default)]
+    struct EffectRuntime {
+        commands: RefCell<Vec<Command<Effect>>>,
+    }
+
+    impl EffectRuntime {
+        fn resolve(&self) -> Effect {
+            match self.commands.borrow_mut().remove(0) {
+                Command::Future(future) => executor::block_on(future),
+                command => panic!("unexpected command: {}", command.kind()),
+            }
+        }
+    }
+
+    impl CommandRuntime<u32> for EffectRuntime {
+        fn spawn_command(
+            &self,
+            _command: Command<u32>,
+            _cancellation_token: Option<CancellationToken>,
+        ) {
+        }
+
+        fn spawn_effect(
+            &self,
+            command: Command<Effect>,
+            _cancellation_token: Option<CancellationToken>,
+        ) {
+            self.commands.borrow_mut().push(command);
+        }
+    }
+
+    fn app(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> impl Element<TestState<u32>, u32, ()> {
+        let key = *context.use_atom(|state| &state.value);
+        Container::new().el(Suspense::new(
+            key,
+            |key: &u32| future::ready(Ok::<_, RenderError>(key * 10)),
+            |value: &u32| Label(format!("value {}", value)).el(()),
+            |_: Option<&RenderError>| Label::new("loading").el(()),
+        ))
+    }
+
+    #[test]
+    fn test_replay_effects() {
+        let path = std::env::temp_dir().join(format!(
+            "yuiui-recording-effects-{}.jsonl",
+            std::process::id()
+        ));
+
+        let mut state = TestState::new(2);
+        let runtime = EffectRuntime::default();
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+        render_loop.add_middleware(
+            Recorder::<u32>::create(&path, &state, PayloadRegistry::new()).unwrap(),
+        );
+
+        render_loop.run_forever(&mut state, &(), &runtime);
+        render_loop.push_effect(runtime.resolve());
+        render_loop.run_forever(&mut state, &(), &runtime);
+        render_loop.push_message(4);
+        render_loop.run_forever(&mut state, &(), &runtime);
+        render_loop.push_effect(runtime.resolve());
+        render_loop.run_forever(&mut state, &(), &runtime);
+        let log = state.log.take();
+        assert_eq!(
+            log,
+            vec![
+                "mount loading",
+                "unmount loading",
+                "mount value 20",
+                "unmount value 20",
+                "mount loading",
+                "unmount loading",
+                "mount value 40",
+            ]
+        );
+        drop(render_loop);
+
+        let session: Session<TestState<u32>, u32> =
+            Session::load(&path, &PayloadRegistry::new()).unwrap();
+        assert_eq!(session.records().len(), 3);
+        let (_, state) = session.replay(app.el(()), &());
+        assert_eq!(*state.value.get(), 4);
+        assert_eq!(state.log.take(), log);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,25 +1,48 @@
+use std::any::Any;
 use std::collections::VecDeque;
-use std::time::{Duration, Instant};
-use std::{cmp, fmt, mem};
+use std::rc::Rc;
+use std::{cmp, fmt, iter, mem};
 
-use crate::command::CommandRuntime;
+use crate::cancellation_token::CancellationToken;
+use crate::command::{CommandRuntime, Commands, SubscriptionKey};
 use crate::component_stack::ComponentStack;
 use crate::context::{CommitContext, RenderContext};
+use crate::deadline::{Deadline, Forever, PendingWork};
 use crate::element::{Element, ElementSeq};
 use crate::event::{EventDestination, EventPayload};
-use crate::id::{IdStack, IdTree, Level};
+use crate::id::{Id, IdStack, IdTree, Level};
+use crate::inspector::Inspection;
+use crate::middleware::Middleware;
+use crate::priority::{Lanes, Priority};
+use crate::profiler::{Phase, Profiler};
+use crate::provider::ProviderRegistry;
 use crate::state::{Effect, State};
+use crate::storages::{BoxedElement, BoxedStorage};
+use crate::subscription::SubscriptionRegistry;
 use crate::view::View;
-use crate::view_node::{CommitMode, ViewNode};
+use crate::view_node::{
+    CommitMode, CommitSubtreeVisitor, InspectVisitor, MulticastEventVisitor, Traversable,
+    UnicastEventVisitor, UpdateSubtreeVisitor, ViewNode, ViewNodeSeq, Visitor,
+};
 
 pub struct RenderLoop<Element: self::Element<S, M, E>, S, M, E> {
     node: ViewNode<Element::View, Element::Components, S, M, E>,
+    roots: Vec<RootNode<S, M, E>>,
+    detached_roots: Vec<RootViewNode<S, M, E>>,
     id_stack: IdStack,
-    message_queue: VecDeque<M>,
-    event_queue: VecDeque<(EventDestination, EventPayload)>,
-    nodes_to_update: IdTree<Level>,
-    nodes_to_commit: IdTree<()>,
+    providers: ProviderRegistry,
+    subscriptions: SubscriptionRegistry,
+    message_queues: Lanes<VecDeque<M>>,
+    event_queues: Lanes<VecDeque<(EventDestination, EventPayload)>>,
+    nodes_to_update: Lanes<IdTree<Level>>,
+    nodes_to_commit: Lanes<IdTree<()>>,
+    update_cursors: Lanes<VecDeque<IdTree<Level>>>,
+    commit_cursors: Lanes<VecDeque<IdTree<()>>>,
     is_mounted: bool,
+    is_initialized: bool,
+    state_subscriptions: Vec<(SubscriptionKey, CancellationToken)>,
+    middlewares: Vec<Box<dyn Middleware<S, M>>>,
+    profiler: Option<Rc<Profiler>>,
 }
 
 impl<Element, S, M, E> RenderLoop<Element, S, M, E>
@@ -28,22 +51,62 @@ where
     S: State<Message = M>,
 {
     pub fn create(element: Element, state: &S) -> Self {
+        Self::create_with_profiler(element, state, None)
+    }
+
+    pub fn create_with_profiler(
+        element: Element,
+        state: &S,
+        profiler: Option<Rc<Profiler>>,
+    ) -> Self {
         let mut id_stack = IdStack::new();
+        let mut providers = ProviderRegistry::new();
+        let subscriptions = SubscriptionRegistry::new();
+        let mut pending_updates = Vec::new();
+        if let Some(profiler) = &profiler {
+            profiler.begin_run();
+        }
+        let span = profiler
+            .as_deref()
+            .map(|profiler| profiler.enter_phase(Phase::Update));
         let mut context = RenderContext {
             id_stack: &mut id_stack,
             state,
             level: Element::Components::LEVEL,
+            local_slots: None,
+            pending_updates: &mut pending_updates,
+            providers: &mut providers,
+            subscriptions: &subscriptions,
+            profiler: profiler.as_deref(),
+            deadline: None,
         };
         let node = element.render(&mut context);
-        Self {
+        subscriptions.stage(node.id);
+        drop(span);
+        if let Some(profiler) = &profiler {
+            profiler.end_run();
+        }
+        let mut render_loop = Self {
             node,
+            roots: Vec::new(),
+            detached_roots: Vec::new(),
             id_stack,
-            message_queue: VecDeque::new(),
-            event_queue: VecDeque::new(),
-            nodes_to_update: IdTree::new(),
-            nodes_to_commit: IdTree::new(),
+            providers,
+            subscriptions,
+            message_queues: Lanes::default(),
+            event_queues: Lanes::default(),
+            nodes_to_update: Lanes::default(),
+            nodes_to_commit: Lanes::default(),
+            update_cursors: Lanes::default(),
+            commit_cursors: Lanes::default(),
             is_mounted: false,
-        }
+            is_initialized: false,
+            state_subscriptions: Vec::new(),
+            middlewares: Vec::new(),
+            profiler,
+        };
+        render_loop.process_effect(Effect::Update(pending_updates), Priority::Normal);
+        render_loop
     }
 
     pub fn run_until(
@@ -51,9 +114,13 @@ where
         state: &mut S,
         entry_point: &E,
         command_runtime: &impl CommandRuntime<M>,
-        deadline: &Instant,
+        deadline: &impl Deadline,
     ) -> RenderFlow {
-        self.run(state, entry_point, command_runtime, deadline)
+        let render_flow = self.run(state, entry_point, command_runtime, deadline);
+        if render_flow == RenderFlow::Suspend {
+            deadline.on_suspend(&self.pending_work());
+        }
+        render_flow
     }
 
     pub fn run_forever(
@@ -67,17 +134,176 @@ where
     }
 
     pub fn push_message(&mut self, message: M) {
-        self.message_queue.push_back(message);
+        self.push_message_with_priority(message, Priority::Normal);
+    }
+
+    pub fn push_message_with_priority(&mut self, message: M, priority: Priority) {
+        for middleware in &mut self.middlewares {
+            middleware.on_message(&message);
+        }
+        self.message_queues[priority].push_back(message);
     }
 
     pub fn push_event(&mut self, destination: EventDestination, payload: EventPayload) {
-        self.event_queue.push_back((destination, payload));
+        self.push_event_with_priority(destination, payload, Priority::Normal);
+    }
+
+    pub fn push_event_with_priority(
+        &mut self,
+        destination: EventDestination,
+        payload: EventPayload,
+        priority: Priority,
+    ) {
+        self.event_queues[priority].push_back((destination, payload));
+    }
+
+    pub fn push_effect(&mut self, effect: Effect) {
+        self.push_effect_with_priority(effect, Priority::Normal);
+    }
+
+    pub fn push_effect_with_priority(&mut self, effect: Effect, priority: Priority) {
+        for middleware in &mut self.middlewares {
+            middleware.on_effect(&effect);
+        }
+        self.process_effect(effect, priority);
+    }
+
+    pub fn add_middleware<T>(&mut self, middleware: T)
+    where
+        T: Middleware<S, M> + 'static,
+    {
+        self.middlewares.push(Box::new(middleware));
+    }
+
+    pub fn add_boxed_middleware(&mut self, middleware: Box<dyn Middleware<S, M>>) {
+        self.middlewares.push(middleware);
+    }
+
+    pub fn flush_middlewares(&mut self, state: &S) {
+        for middleware in &mut self.middlewares {
+            middleware.flush(state);
+        }
+    }
+
+    pub fn pending_work(&self) -> PendingWork {
+        let mut pending_work = PendingWork::default();
+        for priority in Priority::ALL {
+            pending_work.messages += self.message_queues[priority].len();
+            pending_work.events += self.event_queues[priority].len();
+            pending_work.nodes_to_update +=
+                self.nodes_to_update[priority].len() + self.update_cursors[priority].len();
+            pending_work.nodes_to_commit +=
+                self.nodes_to_commit[priority].len() + self.commit_cursors[priority].len();
+        }
+        pending_work
+    }
+
+    // The snapshot goes through the same `Traversable` walk as rendering, which borrows the nodes
+    // mutably and needs the state to select the state of adapted subtrees. It works on its own id
+    // stack and providers, so nothing in the render loop is changed.
+    pub fn inspect(&mut self, state: &S) -> Inspection {
+        let mut id_stack = IdStack::new();
+        let mut pending_updates = Vec::new();
+        let mut providers = ProviderRegistry::new();
+        let mut context = RenderContext {
+            id_stack: &mut id_stack,
+            state,
+            level: Element::Components::LEVEL,
+            local_slots: None,
+            pending_updates: &mut pending_updates,
+            providers: &mut providers,
+            subscriptions: &self.subscriptions,
+            profiler: None,
+            deadline: None,
+        };
+        let mut visitor = InspectVisitor::new();
+        visitor.visit(&mut self.node, &mut context);
+        for root in &mut self.roots {
+            root.node.for_each(&mut visitor, &mut context);
+        }
+        let mut nodes = visitor.into_result().into_iter();
+        Inspection {
+            node: nodes.next().unwrap(),
+            roots: nodes.collect(),
+        }
+    }
+
+    pub fn set_profiler(&mut self, profiler: Option<Rc<Profiler>>) {
+        self.profiler = profiler;
+    }
+
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_deref()
     }
 
     pub fn node(&self) -> &ViewNode<Element::View, Element::Components, S, M, E> {
         &self.node
     }
 
+    pub fn subscription_count(&self, id: Id) -> usize {
+        self.subscriptions.subscription_count(id)
+    }
+
+    pub fn mount_root<T>(&mut self, element: T, state: &S) -> Id
+    where
+        T: self::Element<S, M, E> + 'static,
+        <T::View as View<S, M, E>>::State: 'static,
+        S: 'static,
+        M: 'static,
+        E: 'static,
+    {
+        self.mount_boxed_root(BoxedElement::new(element, |view_state| view_state), state)
+    }
+
+    pub fn mount_boxed_root(&mut self, element: BoxedElement<S, M, E, dyn Any>, state: &S) -> Id
+    where
+        S: 'static,
+        M: 'static,
+        E: 'static,
+    {
+        let level = element.level();
+        let mut pending_updates = Vec::new();
+        let mut context = RenderContext {
+            id_stack: &mut self.id_stack,
+            state,
+            level,
+            local_slots: None,
+            pending_updates: &mut pending_updates,
+            providers: &mut self.providers,
+            subscriptions: &self.subscriptions,
+            profiler: self.profiler.as_deref(),
+            deadline: None,
+        };
+        let node = element.render_children(&mut context);
+        let id = node.id();
+        self.roots.push(RootNode {
+            node,
+            level,
+            is_mounted: false,
+        });
+        self.process_effect(Effect::Update(pending_updates), Priority::Normal);
+        id
+    }
+
+    pub fn unmount_root(&mut self, id: Id) -> bool {
+        if let Some(index) = self.roots.iter().position(|root| root.node.id() == id) {
+            let root = self.roots.remove(index);
+            if root.is_mounted {
+                self.detached_roots.push(root.node);
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn root(&self, id: Id) -> Option<&RootViewNode<S, M, E>> {
+        self.roots
+            .iter()
+            .find(|root| root.node.id() == id)
+            .map(|root| &root.node)
+    }
+
     fn run(
         &mut self,
         state: &mut S,
@@ -85,98 +311,432 @@ where
         command_runtime: &impl CommandRuntime<M>,
         deadline: &impl Deadline,
     ) -> RenderFlow {
-        loop {
-            while let Some(message) = self.message_queue.pop_front() {
-                let effect = state.update(message);
-                self.process_effect(effect);
-                if deadline.did_timeout() {
-                    return self.render_flow();
+        let profiler = self.profiler.clone();
+        if let Some(profiler) = &profiler {
+            profiler.begin_run();
+        }
+        let render_flow = self.process_work(state, entry_point, command_runtime, deadline);
+        if render_flow == RenderFlow::Done {
+            self.subscriptions.discard_staged();
+        }
+        if let Some(profiler) = &profiler {
+            profiler.end_run();
+        }
+        render_flow
+    }
+
+    fn process_work(
+        &mut self,
+        state: &mut S,
+        entry_point: &E,
+        command_runtime: &impl CommandRuntime<M>,
+        deadline: &impl Deadline,
+    ) -> RenderFlow {
+        let profiler = self.profiler.clone();
+        let mut commands = Commands::new();
+
+        if !self.is_initialized {
+            let effect = state.init(&mut commands);
+            self.process_effect(effect, Priority::Normal);
+            self.update_subscriptions(state, command_runtime);
+            self.is_initialized = true;
+        }
+
+        let effect = state.derive();
+        self.process_effect(effect, Priority::Normal);
+
+        self.spawn_commands(mem::take(&mut commands), command_runtime);
+
+        'lanes: loop {
+            for priority in Priority::ALL {
+                while let Some(message) = self.message_queues[priority].pop_front() {
+                    let span = profiler
+                        .as_deref()
+                        .map(|profiler| profiler.enter_phase(Phase::Message));
+                    #[cfg(feature = "tracing")]
+                    let tracing_span = tracing::debug_span!("message", ?priority).entered();
+                    let message = self
+                        .middlewares
+                        .iter_mut()
+                        .try_fold(message, |message, middleware| {
+                            middleware.before_update(message, state)
+                        });
+                    if let Some(message) = message {
+                        let effect = state.update(message, &mut commands).compose(state.derive());
+                        for middleware in &mut self.middlewares {
+                            middleware.after_update(state, &effect);
+                        }
+                        self.process_effect(effect, priority);
+                        self.update_subscriptions(state, command_runtime);
+                        self.spawn_commands(mem::take(&mut commands), command_runtime);
+                    }
+                    drop(span);
+                    #[cfg(feature = "tracing")]
+                    drop(tracing_span);
+                    if deadline.did_timeout() {
+                        return self.render_flow();
+                    }
                 }
-            }
 
-            while let Some((destination, payload)) = self.event_queue.pop_front() {
-                self.process_event(destination, payload, state, entry_point, command_runtime);
-                if deadline.did_timeout() {
-                    return self.render_flow();
+                while let Some((destination, payload)) = self.event_queues[priority].pop_front() {
+                    let span = profiler
+                        .as_deref()
+                        .map(|profiler| profiler.enter_phase(Phase::Event));
+                    #[cfg(feature = "tracing")]
+                    let tracing_span =
+                        tracing::debug_span!("event", ?priority, ?destination).entered();
+                    for middleware in &mut self.middlewares {
+                        middleware.on_event(&destination, &payload);
+                    }
+                    self.process_event(
+                        destination,
+                        payload,
+                        priority,
+                        state,
+                        entry_point,
+                        command_runtime,
+                    );
+                    drop(span);
+                    #[cfg(feature = "tracing")]
+                    drop(tracing_span);
+                    if deadline.did_timeout() {
+                        return self.render_flow();
+                    }
                 }
-            }
 
-            if !self.message_queue.is_empty() {
-                continue;
-            }
+                if !self.message_queues[priority].is_empty() {
+                    continue 'lanes;
+                }
 
-            if !self.nodes_to_update.is_empty() {
-                let id_tree = mem::take(&mut self.nodes_to_update);
-                let mut context = RenderContext {
-                    id_stack: &mut self.id_stack,
-                    state,
-                    level: Element::Components::LEVEL,
-                };
-                let changed_nodes = self.node.update_subtree(&id_tree, &mut context);
-                if self.is_mounted {
-                    for id_path in changed_nodes {
-                        self.nodes_to_commit.insert(&id_path, ());
+                while let Some(id_tree) = self.next_node_to_update(priority) {
+                    self.update_nodes(&id_tree, priority, state, deadline);
+                    if deadline.did_timeout() {
+                        return self.render_flow();
                     }
                 }
-                if deadline.did_timeout() {
-                    return self.render_flow();
-                }
-            }
 
-            if self.is_mounted {
-                if !self.nodes_to_commit.is_empty() {
-                    let id_tree = mem::take(&mut self.nodes_to_commit);
-                    let mut messages = Vec::new();
-                    let mut commands = Vec::new();
-                    let mut context = CommitContext {
-                        id_stack: &mut self.id_stack,
+                while let Some(id_tree) = self.next_node_to_commit(priority) {
+                    self.commit_nodes(
+                        &id_tree,
+                        priority,
                         state,
-                        messages: &mut messages,
-                        commands: &mut commands,
                         entry_point,
-                    };
-                    self.node.commit_subtree(&id_tree, &mut context);
-                    self.message_queue.extend(messages);
-                    for (command, cancellation_token) in commands {
-                        command_runtime.spawn_command(command, cancellation_token);
-                    }
+                        command_runtime,
+                        deadline,
+                    );
                     if deadline.did_timeout() {
                         return self.render_flow();
                     }
                 }
-            } else {
+
+                if self.has_pending_work(priority) {
+                    continue 'lanes;
+                }
+            }
+
+            if !self.is_mounted {
+                let _span = profiler
+                    .as_deref()
+                    .map(|profiler| profiler.enter_phase(Phase::Commit));
+                #[cfg(feature = "tracing")]
+                let _tracing_span = tracing::debug_span!("mount").entered();
                 let mut messages = Vec::new();
-                let mut commands = Vec::new();
+                let mut commands = Commands::new();
+                let mut effect_commands = Commands::new();
+                let mut pending_updates = Vec::new();
                 let mut context = CommitContext {
                     id_stack: &mut self.id_stack,
                     state,
                     messages: &mut messages,
                     commands: &mut commands,
+                    effect_commands: &mut effect_commands,
+                    pending_updates: &mut pending_updates,
+                    providers: &mut self.providers,
+                    subscriptions: &self.subscriptions,
+                    profiler: self.profiler.as_deref(),
                     entry_point,
+                    in_portal: false,
                 };
                 self.node.commit_whole(CommitMode::Mount, &mut context);
-                self.message_queue.extend(messages);
-                for (command, cancellation_token) in commands {
-                    command_runtime.spawn_command(command, cancellation_token);
-                }
+                self.message_queues[Priority::Normal].extend(messages);
+                self.process_effect(Effect::Update(pending_updates), Priority::Normal);
+                self.spawn_commands(commands, command_runtime);
+                self.spawn_effect_commands(effect_commands, command_runtime);
                 self.is_mounted = true;
                 if deadline.did_timeout() {
                     return self.render_flow();
                 }
             }
 
-            if self.message_queue.is_empty() {
+            if !self.detached_roots.is_empty() || self.roots.iter().any(|root| !root.is_mounted) {
+                let _span = profiler
+                    .as_deref()
+                    .map(|profiler| profiler.enter_phase(Phase::Commit));
+                #[cfg(feature = "tracing")]
+                let _tracing_span = tracing::debug_span!(
+                    "mount",
+                    detached_roots = self.detached_roots.len(),
+                    roots = self.roots.len()
+                )
+                .entered();
+                let mut messages = Vec::new();
+                let mut commands = Commands::new();
+                let mut effect_commands = Commands::new();
+                let mut pending_updates = Vec::new();
+                let mut context = CommitContext {
+                    id_stack: &mut self.id_stack,
+                    state,
+                    messages: &mut messages,
+                    commands: &mut commands,
+                    effect_commands: &mut effect_commands,
+                    pending_updates: &mut pending_updates,
+                    providers: &mut self.providers,
+                    subscriptions: &self.subscriptions,
+                    profiler: self.profiler.as_deref(),
+                    entry_point,
+                    in_portal: false,
+                };
+                for mut node in self.detached_roots.drain(..) {
+                    node.commit(CommitMode::Unmount, &mut context);
+                }
+                for root in &mut self.roots {
+                    if !root.is_mounted {
+                        root.node.commit(CommitMode::Mount, &mut context);
+                        root.is_mounted = true;
+                    }
+                }
+                self.message_queues[Priority::Normal].extend(messages);
+                self.process_effect(Effect::Update(pending_updates), Priority::Normal);
+                self.spawn_commands(commands, command_runtime);
+                self.spawn_effect_commands(effect_commands, command_runtime);
+                if deadline.did_timeout() {
+                    return self.render_flow();
+                }
+            }
+
+            if !self.has_pending_work(Priority::Background) {
                 return RenderFlow::Done;
             }
         }
     }
 
-    fn process_effect(&mut self, effect: Effect) {
+    fn next_node_to_update(&mut self, priority: Priority) -> Option<IdTree<Level>> {
+        if self.update_cursors[priority].is_empty() && !self.nodes_to_update[priority].is_empty() {
+            self.update_cursors[priority] = mem::take(&mut self.nodes_to_update[priority])
+                .into_outermost_subtrees(|id| self.roots.iter().any(|root| root.node.id() == id));
+        }
+        self.update_cursors[priority].pop_front()
+    }
+
+    fn next_node_to_commit(&mut self, priority: Priority) -> Option<IdTree<()>> {
+        // Wait until the lane is fully updated so that no half-updated subtree is committed.
+        if !self.is_mounted
+            || !self.update_cursors[priority].is_empty()
+            || !self.nodes_to_update[priority].is_empty()
+            || self.is_commit_blocked(priority)
+        {
+            return None;
+        }
+        if self.commit_cursors[priority].is_empty() && !self.nodes_to_commit[priority].is_empty() {
+            self.commit_cursors[priority] = mem::take(&mut self.nodes_to_commit[priority])
+                .into_outermost_subtrees(|id| self.roots.iter().any(|root| root.node.id() == id));
+        }
+        self.commit_cursors[priority].pop_front()
+    }
+
+    // Committing a subtree also commits the descendants of its dirty nodes, so it has to wait
+    // for other lanes that have updated the same nodes only in part (e.g. when the updates of
+    // their children were put off past the deadline).
+    fn is_commit_blocked(&self, priority: Priority) -> bool {
+        let commits =
+            || iter::once(&self.nodes_to_commit[priority]).chain(&self.commit_cursors[priority]);
+        Priority::ALL
+            .into_iter()
+            .filter(|other| *other != priority && self.is_updating(*other))
+            .any(|other| {
+                iter::once(&self.nodes_to_commit[other])
+                    .chain(&self.commit_cursors[other])
+                    .any(|other_tree| commits().any(|id_tree| id_tree.overlaps(other_tree)))
+            })
+    }
+
+    fn is_updating(&self, priority: Priority) -> bool {
+        (!self.nodes_to_update[priority].is_empty() || !self.update_cursors[priority].is_empty())
+            && (!self.nodes_to_commit[priority].is_empty()
+                || !self.commit_cursors[priority].is_empty())
+    }
+
+    fn update_nodes(
+        &mut self,
+        id_tree: &IdTree<Level>,
+        priority: Priority,
+        state: &S,
+        deadline: &impl Deadline,
+    ) {
+        let profiler = self.profiler.clone();
+        let _span = profiler
+            .as_deref()
+            .map(|profiler| profiler.enter_phase(Phase::Update));
+        #[cfg(feature = "tracing")]
+        let _tracing_span =
+            tracing::debug_span!("update", ?priority, len = id_tree.len()).entered();
+        let mut pending_updates = Vec::new();
+        let mut context = RenderContext {
+            id_stack: &mut self.id_stack,
+            state,
+            level: Element::Components::LEVEL,
+            local_slots: None,
+            pending_updates: &mut pending_updates,
+            providers: &mut self.providers,
+            subscriptions: &self.subscriptions,
+            profiler: self.profiler.as_deref(),
+            deadline: Some(deadline),
+        };
+        let changed_nodes = self.node.update_subtree(id_tree, &mut context);
+        if self.is_mounted {
+            for id_path in changed_nodes {
+                self.nodes_to_commit[priority].insert(&id_path, ());
+            }
+        }
+        for cursor in id_tree.root().children() {
+            let id = cursor.current().id();
+            if let Some(root) = self.roots.iter_mut().find(|root| root.node.id() == id) {
+                let mut visitor = UpdateSubtreeVisitor::new(cursor);
+                root.node.for_id(id, &mut visitor, &mut context);
+                if root.is_mounted {
+                    for id_path in visitor.into_result() {
+                        self.nodes_to_commit[priority].insert(&id_path, ());
+                    }
+                }
+            }
+        }
+        self.process_effect(Effect::Update(pending_updates), priority);
+        let subscribers = self.providers.take_pending_updates();
+        self.process_effect(Effect::Update(subscribers), priority);
+    }
+
+    fn commit_nodes(
+        &mut self,
+        id_tree: &IdTree<()>,
+        priority: Priority,
+        state: &S,
+        entry_point: &E,
+        command_runtime: &impl CommandRuntime<M>,
+        deadline: &impl Deadline,
+    ) {
+        let profiler = self.profiler.clone();
+        let _span = profiler
+            .as_deref()
+            .map(|profiler| profiler.enter_phase(Phase::Commit));
+        #[cfg(feature = "tracing")]
+        let _tracing_span =
+            tracing::debug_span!("commit", ?priority, len = id_tree.len()).entered();
+        let mut messages = Vec::new();
+        let mut commands = Commands::new();
+        let mut effect_commands = Commands::new();
+        let mut pending_updates = Vec::new();
+        let mut context = CommitContext {
+            id_stack: &mut self.id_stack,
+            state,
+            messages: &mut messages,
+            commands: &mut commands,
+            effect_commands: &mut effect_commands,
+            pending_updates: &mut pending_updates,
+            providers: &mut self.providers,
+            subscriptions: &self.subscriptions,
+            profiler: self.profiler.as_deref(),
+            entry_point,
+            in_portal: false,
+        };
+        let mut deferred = self.node.commit_subtree(id_tree, &mut context, deadline);
+        for cursor in id_tree.root().children() {
+            let id = cursor.current().id();
+            if let Some(root) = self.roots.iter_mut().find(|root| root.node.id() == id) {
+                let mut visitor = CommitSubtreeVisitor::new(CommitMode::Update, cursor, &deadline);
+                root.node.for_id(id, &mut visitor, &mut context);
+                deferred.extend(visitor.into_deferred());
+            }
+        }
+        for id_path in deferred {
+            self.nodes_to_commit[priority].insert(&id_path, ());
+        }
+        self.message_queues[priority].extend(messages);
+        self.process_effect(Effect::Update(pending_updates), priority);
+        self.spawn_commands(commands, command_runtime);
+        self.spawn_effect_commands(effect_commands, command_runtime);
+    }
+
+    fn update_subscriptions(&mut self, state: &S, command_runtime: &impl CommandRuntime<M>) {
+        let mut old_subscriptions = mem::take(&mut self.state_subscriptions);
+        for (key, f) in state.subscriptions().entries {
+            if self
+                .state_subscriptions
+                .iter()
+                .any(|(other, _)| *other == key)
+            {
+                continue;
+            }
+            let cancellation_token = match old_subscriptions
+                .iter()
+                .position(|(other, _)| *other == key)
+            {
+                Some(index) => old_subscriptions.swap_remove(index).1,
+                None => {
+                    let cancellation_token = CancellationToken::new();
+                    let mut commands = Commands::new();
+                    commands.push_cancellable(f(), cancellation_token.clone());
+                    self.spawn_commands(commands, command_runtime);
+                    cancellation_token
+                }
+            };
+            self.state_subscriptions.push((key, cancellation_token));
+        }
+        for (_, cancellation_token) in old_subscriptions {
+            cancellation_token.cancel();
+        }
+    }
+
+    fn spawn_commands(&mut self, commands: Commands<M>, command_runtime: &impl CommandRuntime<M>) {
+        for (command, cancellation_token) in commands.entries {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                kind = command.kind(),
+                cancellable = cancellation_token.is_some(),
+                "spawn command"
+            );
+            for middleware in &mut self.middlewares {
+                middleware.on_command(&command, cancellation_token.as_ref());
+            }
+            command_runtime.spawn_command(command, cancellation_token);
+        }
+    }
+
+    fn spawn_effect_commands(
+        &self,
+        effect_commands: Commands<Effect>,
+        command_runtime: &impl CommandRuntime<M>,
+    ) {
+        for (command, cancellation_token) in effect_commands.entries {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                kind = command.kind(),
+                cancellable = cancellation_token.is_some(),
+                "spawn effect command"
+            );
+            command_runtime.spawn_effect(command, cancellation_token);
+        }
+    }
+
+    fn process_effect(&mut self, effect: Effect, priority: Priority) {
+        let nodes_to_update = &mut self.nodes_to_update[priority];
         match effect {
             Effect::Nop => {}
             Effect::Update(subscribers) => {
                 for subscriber in subscribers {
-                    self.nodes_to_update.insert_or_update(
+                    if !self.subscriptions.is_alive(&subscriber.id_path) {
+                        continue;
+                    }
+                    nodes_to_update.insert_or_update(
                         &subscriber.id_path,
                         subscriber.level,
                         cmp::max,
@@ -184,8 +744,16 @@ where
                 }
             }
             Effect::ForceUpdate => {
-                self.nodes_to_update
-                    .insert_or_update(&[], Element::Components::LEVEL, cmp::max);
+                nodes_to_update.insert_or_update(&[], Element::Components::LEVEL, cmp::max);
+                for root in &self.roots {
+                    nodes_to_update.insert_or_update(&[root.node.id()], root.level, cmp::max);
+                }
+                // The state may have been replaced together with its atoms, so subscribers that
+                // are skipped by the update of their ancestors (e.g. memoized ones) have to
+                // subscribe again.
+                for node in self.subscriptions.subscribed_nodes() {
+                    nodes_to_update.insert_or_update(&node.id_path, node.level, cmp::max);
+                }
             }
         }
     }
@@ -194,40 +762,85 @@ where
         &mut self,
         destination: EventDestination,
         payload: EventPayload,
+        priority: Priority,
         state: &mut S,
         entry_point: &E,
         command_runtime: &impl CommandRuntime<M>,
     ) {
         let mut messages = Vec::new();
-        let mut commands = Vec::new();
+        let mut commands = Commands::new();
+        let mut effect_commands = Commands::new();
+        let mut pending_updates = Vec::new();
         let mut context = CommitContext {
             id_stack: &mut self.id_stack,
             state,
             messages: &mut messages,
             commands: &mut commands,
+            effect_commands: &mut effect_commands,
+            pending_updates: &mut pending_updates,
+            providers: &mut self.providers,
+            subscriptions: &self.subscriptions,
+            profiler: self.profiler.as_deref(),
             entry_point,
+            in_portal: false,
         };
         match destination {
             EventDestination::Unicast(destination) => {
-                self.node
-                    .dispatch_unicast_event(&destination, &*payload, &mut context)
+                let root = destination.split_first().and_then(|(&id, tail)| {
+                    self.roots
+                        .iter_mut()
+                        .find(|root| root.node.id() == id)
+                        .map(|root| (root, id, tail))
+                });
+                if let Some((root, id, tail)) = root {
+                    let mut visitor = UnicastEventVisitor::new(&*payload, tail);
+                    root.node.for_id(id, &mut visitor, &mut context);
+                } else {
+                    self.node
+                        .dispatch_unicast_event(&destination, &*payload, &mut context);
+                }
             }
             EventDestination::Multicast(destinations) => {
                 self.node
-                    .dispatch_multicast_event(&destinations, &*payload, &mut context)
+                    .dispatch_multicast_event(&destinations, &*payload, &mut context);
+                if !self.roots.is_empty() {
+                    let id_tree = IdTree::from_iter(&destinations);
+                    for cursor in id_tree.root().children() {
+                        let id = cursor.current().id();
+                        if let Some(root) = self.roots.iter_mut().find(|root| root.node.id() == id)
+                        {
+                            let mut visitor = MulticastEventVisitor::new(&*payload, cursor);
+                            root.node.for_id(id, &mut visitor, &mut context);
+                        }
+                    }
+                }
             }
         }
-        self.message_queue.extend(messages);
-        for (command, cancellation_token) in commands {
-            command_runtime.spawn_command(command, cancellation_token);
-        }
+        self.message_queues[priority].extend(messages);
+        self.process_effect(Effect::Update(pending_updates), priority);
+        self.spawn_commands(commands, command_runtime);
+        self.spawn_effect_commands(effect_commands, command_runtime);
+    }
+
+    fn has_pending_work(&self, lowest_priority: Priority) -> bool {
+        Priority::ALL
+            .into_iter()
+            .take_while(|priority| *priority <= lowest_priority)
+            .any(|priority| {
+                !self.message_queues[priority].is_empty()
+                    || !self.event_queues[priority].is_empty()
+                    || !self.nodes_to_update[priority].is_empty()
+                    || !self.update_cursors[priority].is_empty()
+                    || ((!self.nodes_to_commit[priority].is_empty()
+                        || !self.commit_cursors[priority].is_empty())
+                        && !self.is_commit_blocked(priority))
+            })
     }
 
     fn render_flow(&self) -> RenderFlow {
-        if self.message_queue.is_empty()
-            && self.event_queue.is_empty()
-            && self.nodes_to_update.is_empty()
-            && self.nodes_to_commit.is_empty()
+        if !self.has_pending_work(Priority::Background)
+            && self.detached_roots.is_empty()
+            && self.roots.iter().all(|root| root.is_mounted)
             && self.is_mounted
         {
             RenderFlow::Done
@@ -250,11 +863,36 @@ where
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RenderLoop")
             .field("node", &self.node)
+            .field("roots", &self.roots)
+            .field("detached_roots", &self.detached_roots)
             .field("id_stack", &self.id_stack)
-            .field("message_queue", &self.message_queue)
-            .field("event_queue", &self.event_queue)
+            .field("message_queues", &self.message_queues)
+            .field("event_queues", &self.event_queues)
             .field("nodes_to_update", &self.nodes_to_update)
             .field("nodes_to_commit", &self.nodes_to_commit)
+            .field("update_cursors", &self.update_cursors)
+            .field("commit_cursors", &self.commit_cursors)
+            .field("is_mounted", &self.is_mounted)
+            .field("is_initialized", &self.is_initialized)
+            .field("state_subscriptions", &self.state_subscriptions)
+            .field("middlewares", &self.middlewares.len())
+            .finish()
+    }
+}
+
+type RootViewNode<S, M, E> = BoxedStorage<S, M, E, dyn Any>;
+
+struct RootNode<S, M, E> {
+    node: RootViewNode<S, M, E>,
+    level: Level,
+    is_mounted: bool,
+}
+
+impl<S, M, E> fmt::Debug for RootNode<S, M, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RootNode")
+            .field("node", &self.node)
+            .field("level", &self.level)
             .field("is_mounted", &self.is_mounted)
             .finish()
     }
@@ -266,20 +904,701 @@ pub enum RenderFlow {
     Done,
 }
 
-trait Deadline {
-    fn did_timeout(&self) -> bool;
-}
+#[cfg(test)]
+mod tests {
+    use std::any;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::command::{Command, Subscriptions};
+    use crate::component::FunctionComponent;
+    use crate::component::HigherOrderComponent;
+    use crate::context::RenderContext;
+    use crate::deadline::StepBudget;
+    use crate::element::{ComponentElement, MemoizeElement, ViewElement};
+    use crate::id::NodePath;
+    use crate::profiler::LifecycleProfile;
+    use crate::state::Atom;
+    use crate::test_utils::{counting_token, Container, Label, NullRuntime, TestState};
 
-impl Deadline for Instant {
-    fn did_timeout(&self) -> bool {
-        self.saturating_duration_since(Instant::now()) <= Duration::from_millis(1)
+    fn app(
+        name: &&'static str,
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Label, TestState<u32>, u32, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        Label(format!("{} {}", name, value)).el(())
     }
-}
 
-struct Forever;
+    #[test]
+    fn test_multiple_roots() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el("main"), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount main 0"]);
+
+        let id = render_loop.mount_root(app.el("sub"), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount sub 0"]);
+        assert!(render_loop.root(id).is_some());
+
+        render_loop.push_message(1);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update main 1", "update sub 1"]);
+
+        assert!(render_loop.unmount_root(id));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["unmount sub 1"]);
+        assert!(render_loop.root(id).is_none());
+
+        render_loop.push_message(2);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update main 2"]);
+    }
+
+    fn footer(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Label, TestState<u32>, u32, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        Label(format!("footer {}", value)).el(())
+    }
+
+    #[test]
+    fn test_mount_root_of_different_type() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el("main"), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        state.log.take();
+
+        let id = render_loop.mount_root(footer.el(()), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount footer 0"]);
+        assert!(render_loop
+            .root(id)
+            .and_then(|node| node.view_state())
+            .is_some_and(|view_state| view_state.is::<()>()));
+
+        render_loop.push_message(1);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update main 1", "update footer 1"]);
+
+        render_loop.push_effect(Effect::ForceUpdate);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update main 1", "update footer 1"]);
+    }
+
+    #[test]
+    fn test_unsubscribe_unmounted_nodes() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el("main"), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+
+        let id = render_loop.mount_root(app.el("sub"), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.value.subscriber_count(), 2);
+        assert_eq!(render_loop.subscription_count(id), 1);
+        state.log.take();
+
+        render_loop.unmount_root(id);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.value.subscriber_count(), 1);
+        assert_eq!(render_loop.subscription_count(id), 0);
+        assert_eq!(state.log.take(), vec!["unmount sub 0"]);
+
+        render_loop.push_effect(Effect::Update(vec![NodePath {
+            id_path: vec![id],
+            level: 1,
+        }]));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert!(state.log.take().is_empty());
+    }
+
+    #[test]
+    fn test_state_commands() {
+        #[derive(Default)]
+        struct CommandState {
+            value: Atom<u32>,
+        }
+
+        impl State for CommandState {
+            type Message = u32;
+
+            fn init(&mut self, commands: &mut Commands<Self::Message>) -> Effect {
+                commands.push(Command::delay(Duration::ZERO, || 1));
+                Effect::Nop
+            }
+
+            fn update(
+                &mut self,
+                value: Self::Message,
+                commands: &mut Commands<Self::Message>,
+            ) -> Effect {
+                if value < 3 {
+                    commands.push(Command::delay(Duration::ZERO, move || value + 1));
+                }
+                self.value.set(value)
+            }
+        }
+
+        struct QueueRuntime(RefCell<Vec<Command<u32>>>);
+
+        impl CommandRuntime<u32> for QueueRuntime {
+            fn spawn_command(
+                &self,
+                command: Command<u32>,
+                _cancellation_token: Option<CancellationToken>,
+            ) {
+                self.0.borrow_mut().push(command);
+            }
+
+            fn spawn_effect(
+                &self,
+                _command: Command<Effect>,
+                _cancellation_token: Option<CancellationToken>,
+            ) {
+            }
+        }
+
+        let mut state = CommandState::default();
+        let mut render_loop = RenderLoop::create(Container::<()>::new().el(()), &state);
+        let runtime = QueueRuntime(RefCell::new(Vec::new()));
+
+        let mut messages = Vec::new();
+        render_loop.run_forever(&mut state, &(), &runtime);
+        loop {
+            let command = runtime.0.borrow_mut().pop();
+            let Some(Command::Timeout(_, callback)) = command else {
+                break;
+            };
+            let message = callback();
+            messages.push(message);
+            render_loop.push_message(message);
+            render_loop.run_forever(&mut state, &(), &runtime);
+        }
+
+        assert_eq!(messages, vec![1, 2, 3]);
+        assert_eq!(*state.value.get(), 3);
+    }
+
+    #[test]
+    fn test_state_subscriptions() {
+        #[derive(Default)]
+        struct SubscriptionState {
+            clocks: Vec<u32>,
+        }
+
+        impl State for SubscriptionState {
+            type Message = Vec<u32>;
+
+            fn update(
+                &mut self,
+                clocks: Self::Message,
+                _commands: &mut Commands<Self::Message>,
+            ) -> Effect {
+                self.clocks = clocks;
+                Effect::Nop
+            }
+
+            fn subscriptions(&self) -> Subscriptions<Self::Message> {
+                let mut subscriptions = Subscriptions::new();
+                for &clock in &self.clocks {
+                    subscriptions.push(clock, move || {
+                        Command::every(Duration::from_secs(clock as u64), Vec::new)
+                    });
+                }
+                subscriptions
+            }
+        }
+
+        #[derive(Default)]
+        struct CountingRuntime {
+            spawned: RefCell<Vec<Duration>>,
+            canceled: Arc<AtomicUsize>,
+        }
+
+        impl CommandRuntime<Vec<u32>> for CountingRuntime {
+            fn spawn_command(
+                &self,
+                command: Command<Vec<u32>>,
+                cancellation_token: Option<CancellationToken>,
+            ) {
+                if let Command::Interval(period, _) = command {
+                    self.spawned.borrow_mut().push(period);
+                }
+                cancellation_token
+                    .unwrap()
+                    .register(counting_token(self.canceled.clone()));
+            }
+
+            fn spawn_effect(
+                &self,
+                _command: Command<Effect>,
+                _cancellation_token: Option<CancellationToken>,
+            ) {
+            }
+        }
 
-impl Deadline for Forever {
-    fn did_timeout(&self) -> bool {
-        false
+        let mut state = SubscriptionState { clocks: vec![1] };
+        let mut render_loop = RenderLoop::create(Container::<()>::new().el(()), &state);
+        let runtime = CountingRuntime::default();
+
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert_eq!(runtime.spawned.take(), vec![Duration::from_secs(1)]);
+
+        render_loop.push_message(vec![1, 2]);
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert_eq!(runtime.spawned.take(), vec![Duration::from_secs(2)]);
+        assert_eq!(runtime.canceled.load(Ordering::Relaxed), 0);
+
+        render_loop.push_message(vec![2]);
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert!(runtime.spawned.take().is_empty());
+        assert_eq!(runtime.canceled.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_middleware() {
+        struct TestMiddleware(Vec<String>);
+
+        impl Middleware<TestState<u32>, u32> for TestMiddleware {
+            fn before_update(&mut self, message: u32, _state: &TestState<u32>) -> Option<u32> {
+                self.0.push(format!("before {}", message));
+                match message {
+                    0 => None,
+                    5 => Some(6),
+                    _ => Some(message),
+                }
+            }
+
+            fn after_update(&mut self, state: &TestState<u32>, effect: &Effect) {
+                self.0.push(format!(
+                    "after {} {}",
+                    state.value.get(),
+                    matches!(effect, Effect::Update(_))
+                ));
+            }
+        }
+
+        let middleware = Rc::new(RefCell::new(TestMiddleware(Vec::new())));
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el("main"), &state);
+        render_loop.add_middleware(middleware.clone());
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+
+        render_loop.push_message(0);
+        render_loop.push_message(5);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            mem::take(&mut middleware.borrow_mut().0),
+            vec!["before 0", "before 5", "after 6 true"]
+        );
+        assert_eq!(*state.value.get(), 6);
+    }
+
+    #[test]
+    fn test_priority() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el("main"), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        state.log.take();
+
+        render_loop.push_message_with_priority(1, Priority::Background);
+        render_loop.push_message_with_priority(2, Priority::UserInput);
+
+        let deadline = Instant::now();
+        assert_eq!(
+            render_loop.run_until(&mut state, &(), &NullRuntime, &deadline),
+            RenderFlow::Suspend
+        );
+        assert_eq!(*state.value.get(), 2);
+
+        // The update and the commit of the urgent lane each take one frame.
+        for _ in 0..2 {
+            assert_eq!(
+                render_loop.run_until(&mut state, &(), &NullRuntime, &deadline),
+                RenderFlow::Suspend
+            );
+        }
+        assert_eq!(*state.value.get(), 2);
+        assert_eq!(state.log.take(), vec!["update main 2"]);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(*state.value.get(), 1);
+        assert_eq!(state.log.take(), vec!["update main 1"]);
+    }
+
+    #[test]
+    fn test_time_slicing() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el("main"), &state);
+        render_loop.mount_root(app.el("sub"), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        state.log.take();
+
+        render_loop.push_message(1);
+
+        let deadline = Instant::now();
+        let mut logs = Vec::new();
+        while render_loop.run_until(&mut state, &(), &NullRuntime, &deadline) == RenderFlow::Suspend
+        {
+            logs.push(state.log.take());
+        }
+        logs.push(state.log.take());
+
+        // The message, then the update of each root, then the commit of each root.
+        assert_eq!(
+            logs,
+            vec![
+                vec![],
+                vec![],
+                vec![],
+                vec!["update main 1".to_owned()],
+                vec!["update sub 1".to_owned()],
+            ]
+        );
+    }
+
+    type ItemFn = fn(
+        &usize,
+        &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Label, TestState<u32>, u32, ()>;
+
+    type ItemElement = ComponentElement<
+        FunctionComponent<
+            ItemFn,
+            usize,
+            ViewElement<Label, TestState<u32>, u32, ()>,
+            TestState<u32>,
+            u32,
+            (),
+        >,
+    >;
+
+    type MemoizedListContainer = Container<Vec<MemoizeElement<ItemFn, usize>>>;
+
+    fn item(
+        index: &usize,
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Label, TestState<u32>, u32, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        context.state().push_log(format!("render item {}", index));
+        Label(format!("item {} {}", index, value)).el(())
+    }
+
+    fn list(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Container<Vec<ItemElement>>, TestState<u32>, u32, ()> {
+        context.state().push_log("render list");
+        Container::new().el((0..4).map(|index| (item as ItemFn).el(index)).collect())
+    }
+
+    fn memoized_list(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<MemoizedListContainer, TestState<u32>, u32, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        context.state().push_log(format!("render list {}", value));
+        Container::new().el((0..2)
+            .map(|index| (item as ItemFn).memoize(index))
+            .collect())
+    }
+
+    #[test]
+    fn test_suspend_between_siblings() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(list.el(()), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        state.log.take();
+
+        render_loop.push_effect(Effect::ForceUpdate);
+        let deadline = Instant::now();
+        let mut slices = Vec::new();
+        while render_loop.run_until(&mut state, &(), &NullRuntime, &deadline) == RenderFlow::Suspend
+        {
+            slices.push(state.log.take());
+        }
+        slices.push(state.log.take());
+        assert_eq!(
+            slices,
+            vec![
+                vec!["render list"],
+                vec!["render item 0"],
+                vec!["render item 1"],
+                vec!["render item 2"],
+                vec!["render item 3"],
+                vec![
+                    "update item 0 0",
+                    "update item 1 0",
+                    "update item 2 0",
+                    "update item 3 0"
+                ],
+            ]
+        );
+        assert!(render_loop.pending_work().is_empty());
+    }
+
+    #[test]
+    fn test_commit_after_other_lanes_finish_updating() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(list.el(()), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        state.log.take();
+
+        // The background update renders the list and puts off the updates of its items.
+        render_loop.push_effect_with_priority(Effect::ForceUpdate, Priority::Background);
+        let deadline = Instant::now();
+        assert_eq!(
+            render_loop.run_until(&mut state, &(), &NullRuntime, &deadline),
+            RenderFlow::Suspend
+        );
+        assert_eq!(state.log.take(), vec!["render list"]);
+
+        render_loop.push_message_with_priority(1, Priority::UserInput);
+        let mut slices = Vec::new();
+        while render_loop.run_until(&mut state, &(), &NullRuntime, &deadline) == RenderFlow::Suspend
+        {
+            slices.push(state.log.take());
+        }
+        slices.push(state.log.take());
+        // The user input is committed once the background lane has updated the items as well.
+        assert_eq!(
+            slices,
+            vec![
+                vec![],
+                vec!["render item 0"],
+                vec!["render item 1"],
+                vec!["render item 2"],
+                vec!["render item 3"],
+                vec!["render item 0"],
+                vec!["render item 1"],
+                vec!["render item 2"],
+                vec!["render item 3"],
+                vec!["update item 0 1"],
+                vec!["update item 1 1"],
+                vec!["update item 2 1"],
+                vec!["update item 3 1"],
+                vec![],
+            ]
+        );
+        assert!(render_loop.pending_work().is_empty());
+    }
+
+    #[test]
+    fn test_update_memoized_subscribers_of_updated_node() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(memoized_list.el(()), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        state.log.take();
+
+        render_loop.push_message(1);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec![
+                "render list 1",
+                "render item 0",
+                "render item 1",
+                "update item 0 1",
+                "update item 1 1"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_suspend_between_commits_of_subtree() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(memoized_list.el(()), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        state.log.take();
+
+        render_loop.push_message(1);
+        let deadline = Instant::now();
+        let mut slices = Vec::new();
+        while render_loop.run_until(&mut state, &(), &NullRuntime, &deadline) == RenderFlow::Suspend
+        {
+            slices.push(state.log.take());
+        }
+        slices.push(state.log.take());
+        // The list and its memoized items are committed in separate slices, although they are in
+        // the same subtree.
+        assert_eq!(
+            slices,
+            vec![
+                vec![],
+                vec!["render list 1"],
+                vec!["render item 0"],
+                vec!["render item 1"],
+                vec![],
+                vec!["update item 0 1"],
+                vec!["update item 1 1"],
+            ]
+        );
+        assert!(render_loop.pending_work().is_empty());
+    }
+
+    #[test]
+    fn test_deadline() {
+        struct TestDeadline {
+            budget: StepBudget,
+            reports: RefCell<Vec<PendingWork>>,
+        }
+
+        impl Deadline for TestDeadline {
+            fn did_timeout(&self) -> bool {
+                self.budget.did_timeout()
+            }
+
+            fn on_suspend(&self, pending_work: &PendingWork) {
+                self.reports.borrow_mut().push(*pending_work);
+            }
+        }
+
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el("main"), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+
+        render_loop.push_message(1);
+        render_loop.push_message(2);
+        let deadline = TestDeadline {
+            budget: StepBudget::new(0),
+            reports: RefCell::new(Vec::new()),
+        };
+        assert_eq!(
+            render_loop.run_until(&mut state, &(), &NullRuntime, &deadline),
+            RenderFlow::Suspend
+        );
+        assert_eq!(
+            deadline.reports.take(),
+            vec![PendingWork {
+                messages: 1,
+                events: 0,
+                nodes_to_update: 1,
+                nodes_to_commit: 0,
+            }]
+        );
+
+        let deadline = StepBudget::new(usize::MAX);
+        assert_eq!(
+            render_loop.run_until(&mut state, &(), &NullRuntime, &deadline),
+            RenderFlow::Done
+        );
+        assert!(render_loop.pending_work().is_empty());
+        assert_eq!(*state.value.get(), 2);
+    }
+
+    #[test]
+    fn test_profiler() {
+        let profiler = Rc::new(Profiler::new());
+        let mut state = TestState::default();
+        let mut render_loop =
+            RenderLoop::create_with_profiler(app.el("main"), &state, Some(profiler.clone()));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+
+        render_loop.push_message(1);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+
+        let app_name = any::type_name_of_val(&app);
+        let reports = profiler.take_reports();
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[0].components[app_name].renders, 1);
+        assert!(reports[0].phases.contains_key(&Phase::Update));
+        assert_eq!(
+            reports[1].lifecycles[any::type_name::<Label>()],
+            LifecycleProfile {
+                mounts: 1,
+                ..Default::default()
+            }
+        );
+
+        let report = &reports[2];
+        assert_eq!(report.messages_processed, 1);
+        assert_eq!(report.nodes_visited, 1);
+        assert_eq!(report.nodes_changed, 1);
+        assert_eq!(
+            report.lifecycles[any::type_name::<Label>()],
+            LifecycleProfile {
+                updates: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(report.components[app_name].renders, 1);
+        assert!(report.phases.contains_key(&Phase::Update));
+        assert!(report.phases.contains_key(&Phase::Commit));
+        assert!(report
+            .to_folded_stacks()
+            .lines()
+            .any(|line| line.starts_with(&format!("update;{} ", app_name))));
+    }
+
+    #[test]
+    fn test_inspect() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el("main"), &state);
+
+        let inspection = render_loop.inspect(&state);
+        assert!(inspection.node.dirty);
+        assert!(!inspection.node.mounted);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        let id = render_loop.mount_root(app.el("sub"), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+
+        let inspection = render_loop.inspect(&state);
+        let node = &inspection.node;
+        assert_eq!(node.id_path, Vec::new());
+        assert_eq!(node.view, any::type_name::<Label>());
+        assert_eq!(node.components, vec![any::type_name_of_val(&app)]);
+        assert!(!node.dirty);
+        assert!(node.mounted);
+        assert!(!node.pending);
+        assert_eq!(node.props.as_deref(), Some("Label(\"main 0\")"));
+        assert!(node.children.is_empty());
+        assert_eq!(inspection.roots.len(), 1);
+        assert_eq!(inspection.roots[0].id, id);
+        assert_eq!(inspection.roots[0].id_path, vec![id]);
+        assert_eq!(
+            inspection.roots[0].props.as_deref(),
+            Some("Label(\"sub 0\")")
+        );
+
+        let mut messages = Vec::new();
+        let mut commands = Commands::new();
+        let mut effect_commands = Commands::new();
+        let mut pending_updates = Vec::new();
+        let mut context = CommitContext {
+            id_stack: &mut render_loop.id_stack,
+            state: &state,
+            messages: &mut messages,
+            commands: &mut commands,
+            effect_commands: &mut effect_commands,
+            pending_updates: &mut pending_updates,
+            providers: &mut render_loop.providers,
+            subscriptions: &render_loop.subscriptions,
+            profiler: None,
+            entry_point: &(),
+            in_portal: false,
+        };
+        render_loop.roots[0]
+            .node
+            .commit(CommitMode::Unmount, &mut context);
+        let inspection = render_loop.inspect(&state);
+        assert!(inspection.node.mounted);
+        assert!(!inspection.roots[0].mounted);
+        assert!(render_loop.roots[0].node.view_state().is_some());
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&inspection).unwrap();
+            assert_eq!(
+                serde_json::from_str::<crate::inspector::Inspection>(&json).unwrap(),
+                inspection
+            );
+        }
     }
 }
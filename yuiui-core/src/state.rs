@@ -1,15 +1,31 @@
-use std::cell::RefCell;
 use std::mem;
 
+use crate::command::{Commands, Subscriptions};
 use crate::id::{IdPath, Level, NodePath};
+use crate::subscription::{Subscribers, SubscriptionRegistry};
 
 pub trait State {
     type Message;
 
-    fn update(&mut self, message: Self::Message) -> Effect;
+    // Runs at the start of the first `run_until`, not in `RenderLoop::create`, because creating
+    // the render loop neither mutates the state nor has a command runtime to spawn commands on.
+    fn init(&mut self, _commands: &mut Commands<Self::Message>) -> Effect {
+        Effect::Nop
+    }
+
+    fn update(&mut self, message: Self::Message, commands: &mut Commands<Self::Message>) -> Effect;
+
+    fn subscriptions(&self) -> Subscriptions<Self::Message> {
+        Subscriptions::new()
+    }
+
+    fn derive(&mut self) -> Effect {
+        Effect::Nop
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Effect {
     Nop,
     Update(Vec<NodePath>),
@@ -30,10 +46,21 @@ impl Effect {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Debug, Default)]
 pub struct Atom<T> {
     value: T,
-    subscribers: RefCell<Vec<NodePath>>,
+    version: usize,
+    subscribers: Subscribers,
+}
+
+impl<T: Clone> Clone for Atom<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            version: self.version,
+            subscribers: Subscribers::default(),
+        }
+    }
 }
 
 impl<T> Atom<T> {
@@ -41,7 +68,8 @@ impl<T> Atom<T> {
     pub fn new(value: T) -> Self {
         Self {
             value,
-            subscribers: RefCell::new(Vec::new()),
+            version: 0,
+            subscribers: Subscribers::default(),
         }
     }
 
@@ -50,10 +78,15 @@ impl<T> Atom<T> {
         &self.value
     }
 
+    #[inline]
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
     #[inline]
     pub fn set(&mut self, new_value: T) -> Effect {
         self.value = new_value;
-        Effect::Update(mem::take(self.subscribers.get_mut()))
+        self.notify()
     }
 
     #[inline]
@@ -62,7 +95,7 @@ impl<T> Atom<T> {
         F: FnOnce(&mut T),
     {
         f(&mut self.value);
-        Effect::Update(mem::take(self.subscribers.get_mut()))
+        self.notify()
     }
 
     #[inline]
@@ -71,24 +104,221 @@ impl<T> Atom<T> {
         F: FnOnce(&mut T) -> bool,
     {
         if f(&mut self.value) {
-            Effect::Update(mem::take(self.subscribers.get_mut()))
+            self.notify()
         } else {
             Effect::Nop
         }
     }
 
-    pub(crate) fn subscribe(&self, id_path: &IdPath, level: Level) {
-        for subscriber in self.subscribers.borrow_mut().iter_mut() {
-            if subscriber.id_path == id_path {
-                if subscriber.level < level {
-                    subscriber.level = level;
-                }
-                return;
+    #[inline]
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().len()
+    }
+
+    pub(crate) fn subscribe(
+        &self,
+        id_path: &IdPath,
+        level: Level,
+        subscriptions: &SubscriptionRegistry,
+    ) {
+        subscriptions.subscribe(&self.subscribers, id_path, level);
+    }
+
+    fn notify(&mut self) -> Effect {
+        self.version = self.version.wrapping_add(1);
+        Effect::Update(mem::take(&mut *self.subscribers.lock()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for Atom<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Atom<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Selector<T> {
+    value: T,
+    version: usize,
+    dependency_versions: Option<Vec<usize>>,
+    subscribers: Subscribers,
+}
+
+impl<T: Clone> Clone for Selector<T> {
+    fn clone(&self) -> Self {
+        Self {
+            value: self.value.clone(),
+            version: self.version,
+            dependency_versions: self.dependency_versions.clone(),
+            subscribers: Subscribers::default(),
+        }
+    }
+}
+
+impl<T> Selector<T> {
+    #[inline]
+    pub fn new(initial_value: T) -> Self {
+        Self {
+            value: initial_value,
+            version: 0,
+            dependency_versions: None,
+            subscribers: Subscribers::default(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    #[inline]
+    pub fn version(&self) -> usize {
+        self.version
+    }
+
+    pub fn select<D, F>(&mut self, dependencies: D, f: F) -> Effect
+    where
+        D: Dependency,
+        F: FnOnce(D) -> T,
+        T: PartialEq,
+    {
+        let mut versions = Vec::new();
+        dependencies.collect_versions(&mut versions);
+        if self.dependency_versions.as_ref() == Some(&versions) {
+            return Effect::Nop;
+        }
+        self.dependency_versions = Some(versions);
+        let new_value = f(dependencies);
+        if self.value == new_value {
+            Effect::Nop
+        } else {
+            self.value = new_value;
+            self.version = self.version.wrapping_add(1);
+            Effect::Update(mem::take(&mut *self.subscribers.lock()))
+        }
+    }
+
+    #[inline]
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().len()
+    }
+
+    pub(crate) fn subscribe(
+        &self,
+        id_path: &IdPath,
+        level: Level,
+        subscriptions: &SubscriptionRegistry,
+    ) {
+        subscriptions.subscribe(&self.subscribers, id_path, level);
+    }
+}
+
+pub trait Dependency {
+    fn collect_versions(&self, versions: &mut Vec<usize>);
+}
+
+impl<T> Dependency for &Atom<T> {
+    fn collect_versions(&self, versions: &mut Vec<usize>) {
+        versions.push(self.version);
+    }
+}
+
+impl<T> Dependency for &Selector<T> {
+    fn collect_versions(&self, versions: &mut Vec<usize>) {
+        versions.push(self.version);
+    }
+}
+
+macro_rules! define_dependency_impl {
+    ($($T:ident),*; $($n:tt),*) => {
+        impl<$($T,)*> Dependency for ($($T,)*)
+        where
+            $($T: Dependency,)*
+        {
+            fn collect_versions(&self, versions: &mut Vec<usize>) {
+                $(self.$n.collect_versions(versions);)*
             }
         }
-        self.subscribers.borrow_mut().push(NodePath {
-            id_path: id_path.to_vec(),
-            level,
-        });
+    };
+}
+
+define_dependency_impl!(T0; 0);
+define_dependency_impl!(T0, T1; 0, 1);
+define_dependency_impl!(T0, T1, T2; 0, 1, 2);
+define_dependency_impl!(T0, T1, T2, T3; 0, 1, 2, 3);
+define_dependency_impl!(T0, T1, T2, T3, T4; 0, 1, 2, 3, 4);
+define_dependency_impl!(T0, T1, T2, T3, T4, T5; 0, 1, 2, 3, 4, 5);
+define_dependency_impl!(T0, T1, T2, T3, T4, T5, T6; 0, 1, 2, 3, 4, 5, 6);
+define_dependency_impl!(T0, T1, T2, T3, T4, T5, T6, T7; 0, 1, 2, 3, 4, 5, 6, 7);
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::id::Id;
+
+    #[test]
+    fn test_atom_is_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Atom<u32>>();
+        assert_send::<Selector<u32>>();
+    }
+
+    #[test]
+    fn test_selector() {
+        let mut items = Atom::new(vec![1, 2, 3]);
+        let mut threshold = Atom::new(2);
+        let mut selector = Selector::new(Vec::new());
+        let calls = Cell::new(0);
+        let select =
+            |selector: &mut Selector<Vec<i32>>, items: &Atom<Vec<i32>>, threshold: &Atom<i32>| {
+                selector.select((items, threshold), |(items, threshold)| {
+                    calls.set(calls.get() + 1);
+                    items
+                        .get()
+                        .iter()
+                        .copied()
+                        .filter(|item| item >= threshold.get())
+                        .collect::<Vec<_>>()
+                })
+            };
+
+        assert!(matches!(
+            select(&mut selector, &items, &threshold),
+            Effect::Update(_)
+        ));
+        assert_eq!(selector.get(), &vec![2, 3]);
+        assert_eq!(calls.get(), 1);
+
+        assert!(matches!(
+            select(&mut selector, &items, &threshold),
+            Effect::Nop
+        ));
+        assert_eq!(calls.get(), 1);
+
+        let subscriptions = SubscriptionRegistry::new();
+        selector.subscribe(&[Id::ROOT], 0, &subscriptions);
+        let _ = items.update(|items| items.push(0));
+        assert!(matches!(
+            select(&mut selector, &items, &threshold),
+            Effect::Nop
+        ));
+        assert_eq!(calls.get(), 2);
+
+        let _ = threshold.set(3);
+        assert!(
+            matches!(select(&mut selector, &items, &threshold), Effect::Update(paths) if paths.len() == 1)
+        );
+        assert_eq!(selector.get(), &vec![3]);
+        assert_eq!(calls.get(), 3);
     }
 }
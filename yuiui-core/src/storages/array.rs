@@ -41,7 +41,7 @@ where
     }
 }
 
-impl<'a, V, CS, S, M, E, const N: usize> ViewNodeSeq<S, M, E>
+impl<V, CS, S, M, E, const N: usize> ViewNodeSeq<S, M, E>
     for ArrayStorage<ViewNode<V, CS, S, M, E>, N>
 where
     V: View<S, M, E>,
@@ -0,0 +1,424 @@
+use std::any::{Any, TypeId};
+use std::fmt;
+use std::mem;
+
+use crate::component_stack::ComponentStack;
+use crate::context::{CommitContext, RenderContext};
+use crate::element::{Element, ElementSeq};
+use crate::id::{Id, Level};
+use crate::view::View;
+use crate::view_node::{CommitMode, Traversable, TraversableSeq, ViewNode, ViewNodeSeq};
+
+use super::RenderFlags;
+
+pub type Projection<T, W> = fn(&T) -> &W;
+
+pub struct BoxedElement<S, M, E, W: ?Sized> {
+    inner: Box<dyn AnyElementSeq<S, M, E, W>>,
+}
+
+impl<S, M, E, W> BoxedElement<S, M, E, W>
+where
+    S: 'static,
+    M: 'static,
+    E: 'static,
+    W: ?Sized + 'static,
+{
+    pub fn new<Element>(
+        element: Element,
+        project: Projection<<Element::View as View<S, M, E>>::State, W>,
+    ) -> Self
+    where
+        Element: self::Element<S, M, E> + 'static,
+    {
+        Self {
+            inner: Box::new(ProjectedElement { element, project }),
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        self.inner.level()
+    }
+}
+
+impl<S, M, E, W> ElementSeq<S, M, E> for BoxedElement<S, M, E, W>
+where
+    S: 'static,
+    M: 'static,
+    E: 'static,
+    W: ?Sized + 'static,
+{
+    type Storage = BoxedStorage<S, M, E, W>;
+
+    fn render_children(self, context: &mut RenderContext<S>) -> Self::Storage {
+        BoxedStorage::new(self.inner.render(context))
+    }
+
+    fn update_children(self, storage: &mut Self::Storage, context: &mut RenderContext<S>) -> bool {
+        let type_id = self.inner.node_type_id();
+        if storage.active.node_type_id() == type_id {
+            let was_swapped = storage.flags.contains(RenderFlags::SWAPPED);
+            storage.flags -= RenderFlags::SWAPPED;
+            if self.inner.update(&mut *storage.active, context) {
+                storage.flags |= RenderFlags::UPDATED;
+                true
+            } else {
+                was_swapped
+            }
+        } else {
+            match &mut storage.staging {
+                Some(node) if node.node_type_id() == type_id => {
+                    self.inner.update(&mut **node, context);
+                }
+                staging => {
+                    *staging = Some(self.inner.render(context));
+                }
+            }
+            storage.flags |= RenderFlags::SWAPPED;
+            true
+        }
+    }
+}
+
+impl<S, M, E, W: ?Sized> fmt::Debug for BoxedElement<S, M, E, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BoxedElement").finish_non_exhaustive()
+    }
+}
+
+pub struct BoxedStorage<S, M, E, W: ?Sized> {
+    active: Box<dyn AnyViewNode<S, M, E, W>>,
+    staging: Option<Box<dyn AnyViewNode<S, M, E, W>>>,
+    flags: RenderFlags,
+}
+
+impl<S, M, E, W: ?Sized> BoxedStorage<S, M, E, W> {
+    fn new(active: Box<dyn AnyViewNode<S, M, E, W>>) -> Self {
+        Self {
+            active,
+            staging: None,
+            flags: RenderFlags::NONE,
+        }
+    }
+
+    pub fn id(&self) -> Id {
+        self.active.id()
+    }
+
+    pub fn view_state(&self) -> Option<&W> {
+        self.active.view_state()
+    }
+}
+
+impl<S, M, E, W: ?Sized> ViewNodeSeq<S, M, E> for BoxedStorage<S, M, E, W> {
+    const SIZE_HINT: (usize, Option<usize>) = (1, Some(1));
+
+    fn len(&self) -> usize {
+        1
+    }
+
+    fn commit(&mut self, mode: CommitMode, context: &mut CommitContext<S, M, E>) -> bool {
+        let mut result = false;
+        if self.flags.contains(RenderFlags::SWAPPED) {
+            if self.flags.contains(RenderFlags::COMMITED) {
+                result |= self.active.commit(CommitMode::Unmount, context);
+            }
+            let staging = self.staging.take().unwrap();
+            self.staging = Some(mem::replace(&mut self.active, staging));
+            if mode != CommitMode::Unmount {
+                result |= self.active.commit(CommitMode::Mount, context);
+            }
+        } else if self.flags.contains(RenderFlags::UPDATED) || mode.is_propagable() {
+            result |= self.active.commit(mode, context);
+        }
+        self.flags = RenderFlags::COMMITED;
+        result
+    }
+
+    fn gc(&mut self) {
+        self.active.gc();
+        if !self.flags.contains(RenderFlags::SWAPPED) {
+            self.staging = None;
+        }
+    }
+}
+
+impl<S, M, E, W: ?Sized> fmt::Debug for BoxedStorage<S, M, E, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BoxedStorage")
+            .field("id", &self.active.id())
+            .field("flags", &self.flags)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S, M, E, W, Visitor, Context> Traversable<Visitor, Context> for BoxedStorage<S, M, E, W>
+where
+    W: ?Sized,
+    dyn AnyViewNode<S, M, E, W>: Traversable<Visitor, Context>,
+{
+    fn for_each(&mut self, visitor: &mut Visitor, context: &mut Context) {
+        self.active.for_each(visitor, context)
+    }
+
+    fn for_id(&mut self, id: Id, visitor: &mut Visitor, context: &mut Context) -> bool {
+        self.active.for_id(id, visitor, context)
+    }
+}
+
+trait AnyElementSeq<S, M, E, W: ?Sized> {
+    fn node_type_id(&self) -> TypeId;
+
+    fn level(&self) -> Level;
+
+    fn render(self: Box<Self>, context: &mut RenderContext<S>) -> Box<dyn AnyViewNode<S, M, E, W>>;
+
+    fn update(
+        self: Box<Self>,
+        node: &mut dyn AnyViewNode<S, M, E, W>,
+        context: &mut RenderContext<S>,
+    ) -> bool;
+}
+
+struct ProjectedElement<Element: self::Element<S, M, E>, S, M, E, W: ?Sized> {
+    element: Element,
+    project: Projection<<Element::View as View<S, M, E>>::State, W>,
+}
+
+impl<Element, S, M, E, W> AnyElementSeq<S, M, E, W> for ProjectedElement<Element, S, M, E, W>
+where
+    Element: self::Element<S, M, E> + 'static,
+    S: 'static,
+    M: 'static,
+    E: 'static,
+    W: ?Sized + 'static,
+{
+    fn node_type_id(&self) -> TypeId {
+        TypeId::of::<ViewNode<Element::View, Element::Components, S, M, E>>()
+    }
+
+    fn level(&self) -> Level {
+        Element::Components::LEVEL
+    }
+
+    fn render(self: Box<Self>, context: &mut RenderContext<S>) -> Box<dyn AnyViewNode<S, M, E, W>> {
+        Box::new(ProjectedNode {
+            node: context.render_node(self.element),
+            project: self.project,
+        })
+    }
+
+    fn update(
+        self: Box<Self>,
+        node: &mut dyn AnyViewNode<S, M, E, W>,
+        context: &mut RenderContext<S>,
+    ) -> bool {
+        let node = node
+            .as_any_mut()
+            .downcast_mut::<ViewNode<Element::View, Element::Components, S, M, E>>()
+            .expect("The type of the boxed node does not match the element");
+        context.update_node(self.element, node)
+    }
+}
+
+trait AnyViewNode<S, M, E, W: ?Sized>: TraversableSeq<S, M, E> {
+    fn node_type_id(&self) -> TypeId;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    fn id(&self) -> Id;
+
+    fn view_state(&self) -> Option<&W>;
+
+    fn commit(&mut self, mode: CommitMode, context: &mut CommitContext<S, M, E>) -> bool;
+
+    fn gc(&mut self);
+}
+
+struct ProjectedNode<V: View<S, M, E>, CS: ComponentStack<S, M, E, View = V>, S, M, E, W: ?Sized> {
+    node: ViewNode<V, CS, S, M, E>,
+    project: Projection<V::State, W>,
+}
+
+impl<V, CS, S, M, E, W> AnyViewNode<S, M, E, W> for ProjectedNode<V, CS, S, M, E, W>
+where
+    V: View<S, M, E> + 'static,
+    CS: ComponentStack<S, M, E, View = V> + 'static,
+    S: 'static,
+    M: 'static,
+    E: 'static,
+    W: ?Sized,
+{
+    fn node_type_id(&self) -> TypeId {
+        TypeId::of::<ViewNode<V, CS, S, M, E>>()
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        &mut self.node
+    }
+
+    fn id(&self) -> Id {
+        self.node.id
+    }
+
+    fn view_state(&self) -> Option<&W> {
+        self.node.view_state.as_ref().map(self.project)
+    }
+
+    fn commit(&mut self, mode: CommitMode, context: &mut CommitContext<S, M, E>) -> bool {
+        self.node.commit(mode, context)
+    }
+
+    fn gc(&mut self) {
+        self.node.gc();
+    }
+}
+
+impl<V, CS, S, M, E, W, Visitor, Context> Traversable<Visitor, Context>
+    for ProjectedNode<V, CS, S, M, E, W>
+where
+    V: View<S, M, E>,
+    CS: ComponentStack<S, M, E, View = V>,
+    W: ?Sized,
+    ViewNode<V, CS, S, M, E>: Traversable<Visitor, Context>,
+{
+    fn for_each(&mut self, visitor: &mut Visitor, context: &mut Context) {
+        self.node.for_each(visitor, context)
+    }
+
+    fn for_id(&mut self, id: Id, visitor: &mut Visitor, context: &mut Context) -> bool {
+        self.node.for_id(id, visitor, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::HigherOrderComponent;
+    use crate::deadline::StepBudget;
+    use crate::element::ViewElement;
+    use crate::render_loop::{RenderFlow, RenderLoop};
+    use crate::test_utils::{Container, Label, NullRuntime, TestState};
+
+    type Boxed = BoxedElement<TestState<u32>, u32, (), ()>;
+
+    type LabelElement = ViewElement<Label, TestState<u32>, u32, ()>;
+
+    fn switch(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Container<Boxed>, TestState<u32>, u32, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        let element = if value.is_multiple_of(2) {
+            BoxedElement::new(Label(format!("even {}", value)).el(()), |state| state)
+        } else {
+            BoxedElement::new(
+                Container::<LabelElement>::new().el(Label(format!("odd {}", value)).el(())),
+                |state| state,
+            )
+        };
+        Container::new().el(element)
+    }
+
+    fn counter(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Label, TestState<u32>, u32, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        Label(format!("counter {}", value)).el(())
+    }
+
+    fn toggle(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Container<Boxed>, TestState<u32>, u32, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        let element = if value.is_multiple_of(2) {
+            BoxedElement::new(Label(format!("even {}", value)).el(()), |state| state)
+        } else {
+            BoxedElement::new(counter.el(()), |state| state)
+        };
+        Container::new().el(element)
+    }
+
+    fn app(
+        _props: &(),
+        _context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Container<Boxed>, TestState<u32>, u32, ()> {
+        Container::new().el(BoxedElement::new(counter.el(()), |state| state))
+    }
+
+    #[test]
+    fn test_switch() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(switch.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount even 0"]);
+        let even_id = render_loop.node().children().id();
+
+        render_loop.push_message(1);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["unmount even 0", "mount odd 1"]);
+
+        render_loop.push_message(3);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update odd 3"]);
+
+        render_loop.push_message(2);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec!["unmount odd 3", "remount even 0", "update even 2"]
+        );
+        let storage = render_loop.node().children();
+        assert_eq!(storage.id(), even_id);
+        assert_eq!(storage.flags, RenderFlags::COMMITED);
+    }
+
+    #[test]
+    fn test_discard_staged_node() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(toggle.el(()), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount even 0"]);
+
+        // The counter is rendered into the staging slot but suspended before the commit.
+        render_loop.push_message(1);
+        let render_flow = render_loop.run_until(&mut state, &(), &NullRuntime, &StepBudget::new(1));
+        assert_eq!(render_flow, RenderFlow::Suspend);
+        assert_eq!(state.value.subscriber_count(), 2);
+
+        render_loop.push_message(2);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update even 2"]);
+        assert_eq!(state.value.subscriber_count(), 1);
+
+        // The discarded counter subscribes again while it is rendered before mounting.
+        render_loop.push_message(3);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["unmount even 2", "mount counter 3"]);
+        assert_eq!(state.value.subscriber_count(), 2);
+    }
+
+    #[test]
+    fn test_traverse() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount counter 0"]);
+
+        // The subscribed component inside the boxed node is reached without re-rendering the app.
+        render_loop.push_message(1);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update counter 1"]);
+
+        let inspection = render_loop.inspect(&state);
+        assert_eq!(inspection.node.children.len(), 1);
+        assert_eq!(
+            inspection.node.children[0].props.as_deref(),
+            Some("Label(\"counter 1\")")
+        );
+    }
+}
@@ -0,0 +1,275 @@
+use std::mem;
+
+use crate::context::{CommitContext, RenderContext};
+use crate::either::Either;
+use crate::element::ElementSeq;
+use crate::id::Id;
+use crate::view_node::{CommitMode, Traversable, ViewNodeSeq};
+
+use super::RenderFlags;
+
+#[derive(Debug)]
+pub struct EitherStorage<L, R> {
+    active: Either<L, R>,
+    staging: Option<Either<L, R>>,
+    flags: RenderFlags,
+}
+
+impl<L, R> EitherStorage<L, R> {
+    fn new(active: Either<L, R>) -> Self {
+        Self {
+            active,
+            staging: None,
+            flags: RenderFlags::NONE,
+        }
+    }
+}
+
+impl<L, R, S, M, E> ElementSeq<S, M, E> for Either<L, R>
+where
+    L: ElementSeq<S, M, E>,
+    R: ElementSeq<S, M, E>,
+{
+    type Storage = EitherStorage<L::Storage, R::Storage>;
+
+    fn render_children(self, context: &mut RenderContext<S>) -> Self::Storage {
+        EitherStorage::new(match self {
+            Either::Left(element) => Either::Left(element.render_children(context)),
+            Either::Right(element) => Either::Right(element.render_children(context)),
+        })
+    }
+
+    fn update_children(self, storage: &mut Self::Storage, context: &mut RenderContext<S>) -> bool {
+        match (&mut storage.active, &mut storage.staging, self) {
+            (Either::Left(node), _, Either::Left(element)) => {
+                let was_swapped = storage.flags.contains(RenderFlags::SWAPPED);
+                storage.flags -= RenderFlags::SWAPPED;
+                if element.update_children(node, context) {
+                    storage.flags |= RenderFlags::UPDATED;
+                    true
+                } else {
+                    was_swapped
+                }
+            }
+            (Either::Right(node), _, Either::Right(element)) => {
+                let was_swapped = storage.flags.contains(RenderFlags::SWAPPED);
+                storage.flags -= RenderFlags::SWAPPED;
+                if element.update_children(node, context) {
+                    storage.flags |= RenderFlags::UPDATED;
+                    true
+                } else {
+                    was_swapped
+                }
+            }
+            (Either::Left(_), Some(Either::Right(node)), Either::Right(element)) => {
+                element.update_children(node, context);
+                storage.flags |= RenderFlags::SWAPPED;
+                true
+            }
+            (Either::Right(_), Some(Either::Left(node)), Either::Left(element)) => {
+                element.update_children(node, context);
+                storage.flags |= RenderFlags::SWAPPED;
+                true
+            }
+            (_, staging, Either::Left(element)) => {
+                *staging = Some(Either::Left(element.render_children(context)));
+                storage.flags |= RenderFlags::SWAPPED;
+                true
+            }
+            (_, staging, Either::Right(element)) => {
+                *staging = Some(Either::Right(element.render_children(context)));
+                storage.flags |= RenderFlags::SWAPPED;
+                true
+            }
+        }
+    }
+}
+
+impl<L, R, S, M, E> ViewNodeSeq<S, M, E> for EitherStorage<L, R>
+where
+    L: ViewNodeSeq<S, M, E>,
+    R: ViewNodeSeq<S, M, E>,
+{
+    const SIZE_HINT: (usize, Option<usize>) = {
+        let (left_lower, left_upper) = L::SIZE_HINT;
+        let (right_lower, right_upper) = R::SIZE_HINT;
+        let lower = if left_lower < right_lower {
+            left_lower
+        } else {
+            right_lower
+        };
+        let upper = match (left_upper, right_upper) {
+            (Some(x), Some(y)) if x < y => Some(y),
+            (Some(x), Some(_)) => Some(x),
+            _ => None,
+        };
+        (lower, upper)
+    };
+
+    fn len(&self) -> usize {
+        match &self.active {
+            Either::Left(node) => node.len(),
+            Either::Right(node) => node.len(),
+        }
+    }
+
+    fn commit(&mut self, mode: CommitMode, context: &mut CommitContext<S, M, E>) -> bool {
+        let mut result = false;
+        if self.flags.contains(RenderFlags::SWAPPED) {
+            if self.flags.contains(RenderFlags::COMMITED) {
+                result |= commit_either(&mut self.active, CommitMode::Unmount, context);
+            }
+            let staging = self.staging.take().unwrap();
+            self.staging = Some(mem::replace(&mut self.active, staging));
+            if mode != CommitMode::Unmount {
+                result |= commit_either(&mut self.active, CommitMode::Mount, context);
+            }
+        } else if self.flags.contains(RenderFlags::UPDATED) || mode.is_propagable() {
+            result |= commit_either(&mut self.active, mode, context);
+        }
+        self.flags = RenderFlags::COMMITED;
+        result
+    }
+
+    fn gc(&mut self) {
+        match &mut self.active {
+            Either::Left(node) => node.gc(),
+            Either::Right(node) => node.gc(),
+        }
+        if !self.flags.contains(RenderFlags::SWAPPED) {
+            self.staging = None;
+        }
+    }
+}
+
+impl<L, R, Visitor, Context> Traversable<Visitor, Context> for EitherStorage<L, R>
+where
+    L: Traversable<Visitor, Context>,
+    R: Traversable<Visitor, Context>,
+{
+    fn for_each(&mut self, visitor: &mut Visitor, context: &mut Context) {
+        match &mut self.active {
+            Either::Left(node) => node.for_each(visitor, context),
+            Either::Right(node) => node.for_each(visitor, context),
+        }
+    }
+
+    fn for_id(&mut self, id: Id, visitor: &mut Visitor, context: &mut Context) -> bool {
+        match &mut self.active {
+            Either::Left(node) => node.for_id(id, visitor, context),
+            Either::Right(node) => node.for_id(id, visitor, context),
+        }
+    }
+}
+
+fn commit_either<L, R, S, M, E>(
+    node: &mut Either<L, R>,
+    mode: CommitMode,
+    context: &mut CommitContext<S, M, E>,
+) -> bool
+where
+    L: ViewNodeSeq<S, M, E>,
+    R: ViewNodeSeq<S, M, E>,
+{
+    match node {
+        Either::Left(node) => node.commit(mode, context),
+        Either::Right(node) => node.commit(mode, context),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::component::HigherOrderComponent;
+    use crate::element::ViewElement;
+    use crate::render_loop::{RenderFlow, RenderLoop};
+    use crate::test_utils::{Container, Label, NullRuntime, TestState};
+    use crate::view::View;
+
+    type LabelElement = ViewElement<Label, TestState<u32>, u32, ()>;
+
+    // Three branches are expressed by nesting `Either` in its right branch.
+    type Branches = Either<LabelElement, Either<LabelElement, LabelElement>>;
+
+    fn app(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<Container<Branches>, TestState<u32>, u32, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        Container::new().el(match value % 3 {
+            0 => Either::Left(Label(format!("first {}", value)).el(())),
+            1 => Either::Right(Either::Left(Label(format!("second {}", value)).el(()))),
+            _ => Either::Right(Either::Right(Label(format!("third {}", value)).el(()))),
+        })
+    }
+
+    #[test]
+    fn test_switch() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount first 0"]);
+        let first_id = match &render_loop.node().children().active {
+            Either::Left(node) => node.id,
+            Either::Right(_) => unreachable!(),
+        };
+
+        render_loop.push_message(1);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["unmount first 0", "mount second 1"]);
+
+        render_loop.push_message(4);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update second 4"]);
+
+        render_loop.push_message(2);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["unmount second 4", "mount third 2"]);
+
+        // Switching back remounts the nodes of the previous branch and then updates them.
+        render_loop.push_message(3);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec!["unmount third 2", "remount first 0", "update first 3"]
+        );
+        let storage = render_loop.node().children();
+        assert!(matches!(&storage.active, Either::Left(node) if node.id == first_id));
+        assert!(matches!(&storage.staging, Some(Either::Right(_))));
+        assert_eq!(storage.flags, RenderFlags::COMMITED);
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        state.log.take();
+
+        render_loop.push_message(1);
+
+        // The message and the update each take one frame, and then the commit follows.
+        let deadline = Instant::now();
+        for _ in 0..2 {
+            assert_eq!(
+                render_loop.run_until(&mut state, &(), &NullRuntime, &deadline),
+                RenderFlow::Suspend
+            );
+        }
+        let storage = render_loop.node().children();
+        assert!(storage.active.is_left());
+        assert!(matches!(&storage.staging, Some(Either::Right(node)) if node.active.is_left()));
+        assert!(storage.flags.contains(RenderFlags::SWAPPED));
+        assert!(state.log.take().is_empty());
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        let storage = render_loop.node().children();
+        assert!(storage.active.is_right());
+        assert!(matches!(&storage.staging, Some(Either::Left(_))));
+        assert_eq!(storage.flags, RenderFlags::COMMITED);
+        assert_eq!(state.log.take(), vec!["unmount first 0", "mount second 1"]);
+    }
+}
@@ -0,0 +1,594 @@
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::context::{CommitContext, RenderContext};
+use crate::either::Either;
+use crate::element::ElementSeq;
+use crate::error::RenderError;
+use crate::id::{Id, IdStack, NodePath};
+use crate::view_node::{CommitMode, Traversable, ViewNodeSeq};
+
+use super::RenderFlags;
+
+#[derive(Debug)]
+pub struct ErrorBoundary<T, F, K = ()> {
+    inner: T,
+    fallback: F,
+    reset_key: K,
+}
+
+impl<T, F> ErrorBoundary<T, F> {
+    pub fn new(inner: T, fallback: F) -> Self {
+        Self {
+            inner,
+            fallback,
+            reset_key: (),
+        }
+    }
+}
+
+impl<T, F, K> ErrorBoundary<T, F, K> {
+    pub fn reset_key<NewKey>(self, reset_key: NewKey) -> ErrorBoundary<T, F, NewKey> {
+        ErrorBoundary {
+            inner: self.inner,
+            fallback: self.fallback,
+            reset_key,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ErrorBoundaryStorage<T, U, F, K> {
+    active: Either<T, U>,
+    staging: Option<Either<T, U>>,
+    error: Option<RenderError>,
+    fallback: F,
+    reset_key: K,
+    flags: RenderFlags,
+}
+
+impl<T, U, F, K> ErrorBoundaryStorage<T, U, F, K> {
+    fn new(active: Either<T, U>, error: Option<RenderError>, fallback: F, reset_key: K) -> Self {
+        Self {
+            active,
+            staging: None,
+            error,
+            fallback,
+            reset_key,
+            flags: RenderFlags::NONE,
+        }
+    }
+
+    pub fn error(&self) -> Option<&RenderError> {
+        self.error.as_ref()
+    }
+
+    fn is_broken(&self) -> bool {
+        self.error.is_some() && self.active.is_left()
+    }
+
+    fn fail(
+        &mut self,
+        error: RenderError,
+        id_stack: &IdStack,
+        pending_updates: &mut Vec<NodePath>,
+    ) {
+        self.error = Some(error);
+        pending_updates.push(NodePath {
+            id_path: id_stack.id_path().to_vec(),
+            level: 0,
+        });
+    }
+}
+
+impl<T, F, U, K, S, M, E> ElementSeq<S, M, E> for ErrorBoundary<T, F, K>
+where
+    T: ElementSeq<S, M, E>,
+    F: Fn(&RenderError) -> U,
+    U: ElementSeq<S, M, E>,
+    K: PartialEq,
+{
+    type Storage = ErrorBoundaryStorage<T::Storage, U::Storage, F, K>;
+
+    fn render_children(self, context: &mut RenderContext<S>) -> Self::Storage {
+        let inner = self.inner;
+        match catch_render(context, |context| inner.render_children(context)) {
+            Ok(node) => {
+                ErrorBoundaryStorage::new(Either::Left(node), None, self.fallback, self.reset_key)
+            }
+            Err(error) => {
+                let node = (self.fallback)(&error).render_children(context);
+                ErrorBoundaryStorage::new(
+                    Either::Right(node),
+                    Some(error),
+                    self.fallback,
+                    self.reset_key,
+                )
+            }
+        }
+    }
+
+    fn update_children(self, storage: &mut Self::Storage, context: &mut RenderContext<S>) -> bool {
+        let needs_reset = storage.error.is_some() && storage.reset_key != self.reset_key;
+        storage.fallback = self.fallback;
+        storage.reset_key = self.reset_key;
+
+        // A broken boundary still retries when the reset key changes before the fallback is mounted.
+        if storage.is_broken() && !needs_reset {
+            return true;
+        }
+
+        let inner = self.inner;
+        let ErrorBoundaryStorage {
+            active,
+            staging,
+            error,
+            fallback,
+            flags,
+            ..
+        } = storage;
+        let current = if flags.contains(RenderFlags::SWAPPED) {
+            staging.as_mut().unwrap()
+        } else {
+            &mut *active
+        };
+
+        let result = match (current, needs_reset) {
+            (Either::Left(node), false) => {
+                catch_render(context, |context| inner.update_children(node, context))
+                    .map(|has_changed| mark_updated(flags, has_changed))
+            }
+            (Either::Right(node), false) => {
+                let has_changed = fallback(error.as_ref().unwrap()).update_children(node, context);
+                return mark_updated(flags, has_changed);
+            }
+            (_, true) => {
+                catch_render(context, |context| inner.render_children(context)).map(|node| {
+                    *error = None;
+                    *staging = Some(Either::Left(node));
+                    *flags |= RenderFlags::SWAPPED;
+                    true
+                })
+            }
+        };
+
+        match result {
+            Ok(has_changed) => has_changed,
+            Err(new_error) => {
+                if flags.contains(RenderFlags::SWAPPED) {
+                    *staging = None;
+                    *flags -= RenderFlags::SWAPPED;
+                }
+                let error = error.insert(new_error);
+                if let Either::Right(node) = active {
+                    fallback(error).update_children(node, context);
+                }
+                mark_updated(flags, true)
+            }
+        }
+    }
+}
+
+impl<T, U, F, K, Fallback, S, M, E> ViewNodeSeq<S, M, E> for ErrorBoundaryStorage<T, U, F, K>
+where
+    T: ViewNodeSeq<S, M, E>,
+    U: ViewNodeSeq<S, M, E>,
+    F: Fn(&RenderError) -> Fallback,
+    Fallback: ElementSeq<S, M, E, Storage = U>,
+{
+    const SIZE_HINT: (usize, Option<usize>) = {
+        let (inner_lower, inner_upper) = T::SIZE_HINT;
+        let (fallback_lower, fallback_upper) = U::SIZE_HINT;
+        let lower = if inner_lower < fallback_lower {
+            inner_lower
+        } else {
+            fallback_lower
+        };
+        let upper = match (inner_upper, fallback_upper) {
+            (Some(x), Some(y)) if x < y => Some(y),
+            (Some(x), Some(_)) => Some(x),
+            _ => None,
+        };
+        (lower, upper)
+    };
+
+    fn len(&self) -> usize {
+        match &self.active {
+            Either::Left(node) => node.len(),
+            Either::Right(node) => node.len(),
+        }
+    }
+
+    fn commit(&mut self, mode: CommitMode, context: &mut CommitContext<S, M, E>) -> bool {
+        let mut result = false;
+        if self.flags.contains(RenderFlags::SWAPPED) {
+            if self.flags.contains(RenderFlags::COMMITED) {
+                result |= self.commit_active(CommitMode::Unmount, context);
+            }
+            self.active = self.staging.take().unwrap();
+            if mode != CommitMode::Unmount {
+                result |= self.commit_active(CommitMode::Mount, context);
+            }
+        } else if self.is_broken() {
+            if self.flags.contains(RenderFlags::COMMITED) {
+                self.unmount_broken(context);
+            }
+            if mode != CommitMode::Unmount {
+                self.mount_fallback(context);
+            }
+            result = true;
+        } else if self.flags.contains(RenderFlags::UPDATED) || mode.is_propagable() {
+            result |= self.commit_active(mode, context);
+        }
+        self.flags = RenderFlags::COMMITED;
+        result
+    }
+
+    fn gc(&mut self) {
+        match &mut self.active {
+            Either::Left(node) => node.gc(),
+            Either::Right(node) => node.gc(),
+        }
+        if !self.flags.contains(RenderFlags::SWAPPED) {
+            self.staging = None;
+        }
+    }
+}
+
+impl<T, U, F, K> ErrorBoundaryStorage<T, U, F, K> {
+    fn commit_active<Fallback, S, M, E>(
+        &mut self,
+        mode: CommitMode,
+        context: &mut CommitContext<S, M, E>,
+    ) -> bool
+    where
+        T: ViewNodeSeq<S, M, E>,
+        U: ViewNodeSeq<S, M, E>,
+        F: Fn(&RenderError) -> Fallback,
+        Fallback: ElementSeq<S, M, E, Storage = U>,
+    {
+        match &mut self.active {
+            Either::Left(node) => match catch_commit(context, |context| node.commit(mode, context))
+            {
+                Ok(has_changed) => has_changed,
+                Err(error) => {
+                    self.error = Some(error);
+                    self.unmount_broken(context);
+                    if mode != CommitMode::Unmount {
+                        self.mount_fallback(context);
+                    }
+                    true
+                }
+            },
+            Either::Right(node) => node.commit(mode, context),
+        }
+    }
+
+    fn unmount_broken<S, M, E>(&mut self, context: &mut CommitContext<S, M, E>)
+    where
+        T: ViewNodeSeq<S, M, E>,
+    {
+        if let Either::Left(node) = &mut self.active {
+            let _ = catch_commit(context, |context| node.commit(CommitMode::Unmount, context));
+        }
+    }
+
+    fn mount_fallback<Fallback, S, M, E>(&mut self, context: &mut CommitContext<S, M, E>)
+    where
+        U: ViewNodeSeq<S, M, E>,
+        F: Fn(&RenderError) -> Fallback,
+        Fallback: ElementSeq<S, M, E, Storage = U>,
+    {
+        let element = (self.fallback)(self.error.as_ref().unwrap());
+        let mut node = element.render_children(&mut context.render_context());
+        node.commit(CommitMode::Mount, context);
+        self.active = Either::Right(node);
+    }
+}
+
+impl<'context, T, U, F, K, S, Visitor> Traversable<Visitor, RenderContext<'context, S>>
+    for ErrorBoundaryStorage<T, U, F, K>
+where
+    T: Traversable<Visitor, RenderContext<'context, S>>,
+    U: Traversable<Visitor, RenderContext<'context, S>>,
+{
+    fn for_each(&mut self, visitor: &mut Visitor, context: &mut RenderContext<'context, S>) {
+        let result = match &mut self.active {
+            Either::Left(_) if self.error.is_some() => Ok(()),
+            Either::Left(node) => catch_render(context, |context| node.for_each(visitor, context)),
+            Either::Right(node) => {
+                node.for_each(visitor, context);
+                Ok(())
+            }
+        };
+        if let Err(error) = result {
+            self.fail(error, context.id_stack, context.pending_updates);
+        }
+    }
+
+    fn for_id(
+        &mut self,
+        id: Id,
+        visitor: &mut Visitor,
+        context: &mut RenderContext<'context, S>,
+    ) -> bool {
+        let result = match &mut self.active {
+            Either::Left(_) if self.error.is_some() => Ok(false),
+            Either::Left(node) => {
+                catch_render(context, |context| node.for_id(id, visitor, context))
+            }
+            Either::Right(node) => Ok(node.for_id(id, visitor, context)),
+        };
+        result.unwrap_or_else(|error| {
+            self.fail(error, context.id_stack, context.pending_updates);
+            true
+        })
+    }
+}
+
+impl<'context, T, U, F, K, S, M, E, Visitor> Traversable<Visitor, CommitContext<'context, S, M, E>>
+    for ErrorBoundaryStorage<T, U, F, K>
+where
+    T: Traversable<Visitor, CommitContext<'context, S, M, E>>,
+    U: Traversable<Visitor, CommitContext<'context, S, M, E>>,
+{
+    fn for_each(&mut self, visitor: &mut Visitor, context: &mut CommitContext<'context, S, M, E>) {
+        let result = match &mut self.active {
+            Either::Left(_) if self.error.is_some() => Ok(()),
+            Either::Left(node) => catch_commit(context, |context| node.for_each(visitor, context)),
+            Either::Right(node) => {
+                node.for_each(visitor, context);
+                Ok(())
+            }
+        };
+        if let Err(error) = result {
+            self.fail(error, context.id_stack, context.pending_updates);
+        }
+    }
+
+    fn for_id(
+        &mut self,
+        id: Id,
+        visitor: &mut Visitor,
+        context: &mut CommitContext<'context, S, M, E>,
+    ) -> bool {
+        let result = match &mut self.active {
+            Either::Left(_) if self.error.is_some() => Ok(false),
+            Either::Left(node) => {
+                catch_commit(context, |context| node.for_id(id, visitor, context))
+            }
+            Either::Right(node) => Ok(node.for_id(id, visitor, context)),
+        };
+        result.unwrap_or_else(|error| {
+            self.fail(error, context.id_stack, context.pending_updates);
+            true
+        })
+    }
+}
+
+fn catch_render<'context, F, T, S>(
+    context: &mut RenderContext<'context, S>,
+    f: F,
+) -> Result<T, RenderError>
+where
+    F: FnOnce(&mut RenderContext<'context, S>) -> T,
+{
+    let depth = context.id_stack.id_path().len();
+    let level = context.level;
+    let local_slots = mem::take(&mut context.local_slots);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| f(context))).map_err(|payload| {
+        context.id_stack.truncate(depth);
+        context.level = level;
+        RenderError::from_panic(payload)
+    });
+    context.local_slots = local_slots;
+    result
+}
+
+fn catch_commit<'context, F, T, S, M, E>(
+    context: &mut CommitContext<'context, S, M, E>,
+    f: F,
+) -> Result<T, RenderError>
+where
+    F: FnOnce(&mut CommitContext<'context, S, M, E>) -> T,
+{
+    let depth = context.id_stack.id_path().len();
+    panic::catch_unwind(AssertUnwindSafe(|| f(context))).map_err(|payload| {
+        context.id_stack.truncate(depth);
+        RenderError::from_panic(payload)
+    })
+}
+
+fn mark_updated(flags: &mut RenderFlags, has_changed: bool) -> bool {
+    if flags.contains(RenderFlags::SWAPPED) {
+        true
+    } else {
+        if has_changed {
+            *flags |= RenderFlags::UPDATED;
+        }
+        has_changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::{FunctionComponent, HigherOrderComponent};
+    use crate::deadline::StepBudget;
+    use crate::element::{ComponentElement, ViewElement};
+    use crate::event::{EventTarget, Lifecycle};
+    use crate::render_loop::{RenderFlow, RenderLoop};
+    use crate::test_utils::{lifecycle_name, Container, Label, NullRuntime, TestState};
+    use crate::view::View;
+
+    // The value to render and the reset key of the boundary.
+    type Message = (u32, u32);
+
+    type ChildElement = ViewElement<Fragile, TestState<Message>, Message, ()>;
+
+    type ChildFn =
+        fn(&(), &mut RenderContext<TestState<Message>>) -> Result<ChildElement, RenderError>;
+
+    type FallbackFn = fn(&RenderError) -> ViewElement<Label, TestState<Message>, Message, ()>;
+
+    type Boundary = ErrorBoundary<
+        ComponentElement<
+            FunctionComponent<
+                ChildFn,
+                (),
+                Result<ChildElement, RenderError>,
+                TestState<Message>,
+                Message,
+                (),
+            >,
+        >,
+        FallbackFn,
+        u32,
+    >;
+
+    struct Fragile(String);
+
+    impl View<TestState<Message>, Message, ()> for Fragile {
+        type Children = ();
+
+        type State = ();
+
+        fn lifecycle(
+            &self,
+            lifecycle: Lifecycle<Self>,
+            _view_state: &mut Self::State,
+            _children: &mut <Self::Children as ElementSeq<TestState<Message>, Message, ()>>::Storage,
+            context: &mut CommitContext<TestState<Message>, Message, ()>,
+        ) {
+            context
+                .state()
+                .push_log(format!("{} {}", lifecycle_name(&lifecycle), self.0));
+            if self.0 == "boom" {
+                panic!("boom");
+            }
+        }
+
+        fn build(
+            &self,
+            _children: &mut <Self::Children as ElementSeq<TestState<Message>, Message, ()>>::Storage,
+            _context: &mut CommitContext<TestState<Message>, Message, ()>,
+        ) -> Self::State {
+        }
+    }
+
+    impl<'event> EventTarget<'event> for Fragile {
+        type Event = ();
+    }
+
+    fn child(
+        _props: &(),
+        context: &mut RenderContext<TestState<Message>>,
+    ) -> Result<ChildElement, RenderError> {
+        match context.use_atom(|state| &state.value).0 {
+            4 => Ok(Fragile("boom".to_owned()).el(())),
+            value if value % 2 == 1 => Err(RenderError::new(format!("odd {}", value))),
+            value => Ok(Fragile(format!("value {}", value)).el(())),
+        }
+    }
+
+    fn fallback(error: &RenderError) -> ViewElement<Label, TestState<Message>, Message, ()> {
+        Label(format!("error: {}", error)).el(())
+    }
+
+    fn app(
+        _props: &(),
+        context: &mut RenderContext<TestState<Message>>,
+    ) -> ViewElement<Container<Boundary>, TestState<Message>, Message, ()> {
+        let retry = context.use_atom(|state| &state.value).1;
+        Container::new().el(
+            ErrorBoundary::new((child as ChildFn).el(()), fallback as FallbackFn).reset_key(retry),
+        )
+    }
+
+    #[test]
+    fn test_error_boundary() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount value 0"]);
+
+        render_loop.push_message((1, 0));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec!["unmount value 0", "mount error: odd 1"]
+        );
+
+        // The broken child stays unmounted until the reset key changes.
+        render_loop.push_message((2, 0));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update error: odd 1"]);
+
+        render_loop.push_message((2, 1));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec!["unmount error: odd 1", "mount value 2"]
+        );
+
+        render_loop.push_message((4, 1));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec!["update boom", "unmount boom", "mount error: boom"]
+        );
+    }
+
+    #[test]
+    fn test_fallback_without_rerendering_ancestors() {
+        fn stable_app(
+            _props: &(),
+            context: &mut RenderContext<TestState<Message>>,
+        ) -> ViewElement<Container<Boundary>, TestState<Message>, Message, ()> {
+            context.state().push_log("render app");
+            Container::new().el(ErrorBoundary::new(
+                (child as ChildFn).el(()),
+                fallback as FallbackFn,
+            )
+            .reset_key(0))
+        }
+
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(stable_app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["render app", "mount value 0"]);
+
+        // Only the child is subscribed, so the failure is caught while updating the child alone.
+        render_loop.push_message((1, 0));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec!["unmount value 0", "mount error: odd 1"]
+        );
+        assert!(render_loop.node().children().error().is_some());
+    }
+
+    #[test]
+    fn test_reset_before_fallback_is_mounted() {
+        let mut state = TestState::default();
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount value 0"]);
+
+        // The child fails while updating and the run is suspended before the fallback is mounted.
+        render_loop.push_message((1, 0));
+        assert_eq!(
+            render_loop.run_until(&mut state, &(), &NullRuntime, &StepBudget::new(2)),
+            RenderFlow::Suspend
+        );
+        assert!(render_loop.node().children().error().is_some());
+        assert_eq!(state.log.take(), Vec::<String>::new());
+
+        render_loop.push_message((2, 1));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["unmount value 0", "mount value 2"]);
+    }
+}
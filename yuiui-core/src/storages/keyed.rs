@@ -0,0 +1,304 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::mem;
+
+use crate::component_stack::ComponentStack;
+use crate::context::{CommitContext, RenderContext};
+use crate::element::{Element, ElementSeq};
+use crate::id::Id;
+use crate::view::View;
+use crate::view_node::{CommitMode, Traversable, ViewNode, ViewNodeSeq};
+
+/// Children reconciled by key.
+///
+/// # Panics
+///
+/// Rendering or updating panics with "Keys of the keyed sequence must be unique" if two items
+/// share the same key.
+#[derive(Debug)]
+pub struct Keyed<K, T> {
+    items: Vec<(K, T)>,
+}
+
+impl<K, T> Keyed<K, T> {
+    pub fn new(items: Vec<(K, T)>) -> Self {
+        Self { items }
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+}
+
+impl<K, T> From<Vec<(K, T)>> for Keyed<K, T> {
+    fn from(items: Vec<(K, T)>) -> Self {
+        Self::new(items)
+    }
+}
+
+impl<K, T> FromIterator<(K, T)> for Keyed<K, T> {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (K, T)>,
+    {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+#[derive(Debug)]
+pub struct KeyedStorage<K, T> {
+    active: Vec<(K, T)>,
+    staging: HashMap<K, T>,
+    new_keys: Vec<K>,
+    dirty: bool,
+}
+
+impl<K, T> KeyedStorage<K, T>
+where
+    K: Clone + Eq + Hash,
+{
+    fn new(active: Vec<(K, T)>) -> Self {
+        Self {
+            new_keys: active.iter().map(|(key, _)| key.clone()).collect(),
+            staging: HashMap::new(),
+            active,
+            dirty: true,
+        }
+    }
+}
+
+impl<K, Element, S, M, E> ElementSeq<S, M, E> for Keyed<K, Element>
+where
+    K: Clone + Eq + Hash,
+    Element: self::Element<S, M, E>,
+{
+    type Storage = KeyedStorage<K, ViewNode<Element::View, Element::Components, S, M, E>>;
+
+    fn render_children(self, context: &mut RenderContext<S>) -> Self::Storage {
+        assert_unique_keys(&self.items);
+        KeyedStorage::new(
+            self.items
+                .into_iter()
+                .map(|(key, element)| (key, context.render_node(element)))
+                .collect(),
+        )
+    }
+
+    fn update_children(self, storage: &mut Self::Storage, context: &mut RenderContext<S>) -> bool {
+        assert_unique_keys(&self.items);
+
+        let mut has_changed = storage.active.len() != self.items.len();
+
+        let positions = {
+            let indices = storage
+                .active
+                .iter()
+                .enumerate()
+                .map(|(i, (key, _))| (key, i))
+                .collect::<HashMap<_, _>>();
+            self.items
+                .iter()
+                .map(|(key, _)| indices.get(key).copied())
+                .collect::<Vec<_>>()
+        };
+
+        let mut new_keys = Vec::with_capacity(self.items.len());
+
+        for (i, ((key, element), position)) in self.items.into_iter().zip(positions).enumerate() {
+            if let Some(position) = position {
+                let (_, node) = &mut storage.active[position];
+                has_changed |= context.update_node(element, node);
+                has_changed |= position != i;
+            } else if let Some(node) = storage.staging.get_mut(&key) {
+                context.update_node(element, node);
+                has_changed = true;
+            } else {
+                let node = context.render_node(element);
+                storage.staging.insert(key.clone(), node);
+                has_changed = true;
+            }
+            new_keys.push(key);
+        }
+
+        storage.new_keys = new_keys;
+        storage.dirty |= has_changed;
+
+        has_changed
+    }
+}
+
+fn assert_unique_keys<K: Eq + Hash, T>(items: &[(K, T)]) {
+    let mut keys = HashSet::with_capacity(items.len());
+    for (key, _) in items {
+        assert!(
+            keys.insert(key),
+            "Keys of the keyed sequence must be unique"
+        );
+    }
+}
+
+impl<K, V, CS, S, M, E> ViewNodeSeq<S, M, E> for KeyedStorage<K, ViewNode<V, CS, S, M, E>>
+where
+    K: Clone + Eq + Hash,
+    V: View<S, M, E>,
+    CS: ComponentStack<S, M, E, View = V>,
+{
+    const SIZE_HINT: (usize, Option<usize>) = (0, None);
+
+    fn len(&self) -> usize {
+        self.active.len()
+    }
+
+    fn commit(&mut self, mode: CommitMode, context: &mut CommitContext<S, M, E>) -> bool {
+        let mut result = false;
+        if self.dirty || mode.is_propagable() {
+            let new_indices = self
+                .new_keys
+                .iter()
+                .enumerate()
+                .map(|(i, key)| (key, i))
+                .collect::<HashMap<_, _>>();
+            let mut slots = Vec::with_capacity(self.new_keys.len());
+            slots.resize_with(self.new_keys.len(), || None);
+
+            for (old_index, (key, mut node)) in mem::take(&mut self.active).into_iter().enumerate()
+            {
+                if let Some(&new_index) = new_indices.get(&key) {
+                    result |= old_index != new_index;
+                    slots[new_index] = Some((key, node));
+                } else {
+                    node.commit(CommitMode::Unmount, context);
+                    result = true;
+                }
+            }
+
+            for (key, slot) in self.new_keys.iter().zip(slots) {
+                match slot {
+                    Some((key, mut node)) => {
+                        result |= node.commit(mode, context);
+                        self.active.push((key, node));
+                    }
+                    None if mode != CommitMode::Unmount => {
+                        let (key, mut node) = self
+                            .staging
+                            .remove_entry(key)
+                            .expect("The node of a new key must be staged");
+                        node.commit(CommitMode::Mount, context);
+                        self.active.push((key, node));
+                        result = true;
+                    }
+                    None => {}
+                }
+            }
+
+            self.dirty = false;
+        }
+        result
+    }
+
+    fn gc(&mut self) {
+        let new_keys = self.new_keys.iter().collect::<HashSet<_>>();
+        self.staging.retain(|key, _| new_keys.contains(key));
+        for (_, node) in &mut self.active {
+            node.gc();
+        }
+        for node in self.staging.values_mut() {
+            node.gc();
+        }
+    }
+}
+
+impl<Visitor, Context, K, V, CS, S, M, E> Traversable<Visitor, Context>
+    for KeyedStorage<K, ViewNode<V, CS, S, M, E>>
+where
+    V: View<S, M, E>,
+    CS: ComponentStack<S, M, E, View = V>,
+    ViewNode<V, CS, S, M, E>: Traversable<Visitor, Context> + ViewNodeSeq<S, M, E>,
+{
+    fn for_each(&mut self, visitor: &mut Visitor, context: &mut Context) {
+        for (_, node) in &mut self.active {
+            node.for_each(visitor, context);
+        }
+    }
+
+    fn for_id(&mut self, id: Id, visitor: &mut Visitor, context: &mut Context) -> bool {
+        if let Some((_, node)) = self.active.iter_mut().find(|(_, node)| node.id == id) {
+            return node.for_id(id, visitor, context);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::HigherOrderComponent;
+    use crate::element::ViewElement;
+    use crate::render_loop::RenderLoop;
+    use crate::test_utils::{Container, Label, NullRuntime, TestState};
+
+    type Message = Vec<u32>;
+
+    type ItemElement = ViewElement<Label, TestState<Message>, Message, ()>;
+
+    fn list(
+        _props: &(),
+        context: &mut RenderContext<TestState<Message>>,
+    ) -> ViewElement<Container<Keyed<u32, ItemElement>>, TestState<Message>, Message, ()> {
+        let keys = context.use_atom(|state| &state.value);
+        Container::new().el(keys
+            .iter()
+            .map(|&key| (key, Label(key.to_string()).el(())))
+            .collect())
+    }
+
+    fn node_ids<CS: ComponentStack<TestState<Message>, Message, (), View = Label>>(
+        storage: &KeyedStorage<u32, ViewNode<Label, CS, TestState<Message>, Message, ()>>,
+    ) -> Vec<(u32, Id)> {
+        storage
+            .active
+            .iter()
+            .map(|(key, node)| (*key, node.id))
+            .collect()
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let mut state = TestState::new(vec![1, 2, 3]);
+        let mut render_loop = RenderLoop::create(list.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount 1", "mount 2", "mount 3"]);
+        let ids = node_ids(render_loop.node().children());
+        let (id_2, id_3) = (ids[1].1, ids[2].1);
+
+        render_loop.push_message(vec![2, 3, 4]);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec!["unmount 1", "update 2", "update 3", "mount 4"]
+        );
+        let ids = node_ids(render_loop.node().children());
+        assert_eq!(&ids[..2], &[(2, id_2), (3, id_3)]);
+        let id_4 = ids[2].1;
+
+        render_loop.push_message(vec![4, 3, 2]);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update 4", "update 3", "update 2"]);
+        assert_eq!(
+            node_ids(render_loop.node().children()),
+            vec![(4, id_4), (3, id_3), (2, id_2)]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Keys of the keyed sequence must be unique")]
+    fn test_duplicate_keys() {
+        let state = TestState::new(vec![1, 2, 1]);
+        RenderLoop::create(list.el(()), &state);
+    }
+}
@@ -1,9 +1,21 @@
 mod array;
+mod boxed;
+mod either;
+mod error_boundary;
 mod hlist;
+mod keyed;
 mod option;
+mod portal;
+mod suspense;
 mod tuple;
 mod vec;
 
+pub use boxed::{BoxedElement, BoxedStorage, Projection};
+pub use error_boundary::{ErrorBoundary, ErrorBoundaryStorage};
+pub use keyed::Keyed;
+pub use portal::{Portal, PortalStorage, PortalTarget};
+pub use suspense::{Suspense, SuspenseStorage};
+
 use bitflags::bitflags;
 
 bitflags! {
@@ -0,0 +1,334 @@
+use std::mem;
+
+use crate::context::{CommitContext, RenderContext};
+use crate::element::ElementSeq;
+use crate::id::Id;
+use crate::view_node::{CommitMode, Traversable, ViewNodeSeq};
+
+pub trait PortalTarget<T, S, M, E>: Sized {
+    fn attach(&mut self, children: &mut T, context: &mut CommitContext<S, M, E>);
+
+    fn update(&mut self, _children: &mut T, _context: &mut CommitContext<S, M, E>) {}
+
+    fn retarget(&mut self, target: Self, children: &mut T, context: &mut CommitContext<S, M, E>) {
+        self.detach(children, context);
+        *self = target;
+        self.attach(children, context);
+    }
+
+    fn detach(&mut self, children: &mut T, context: &mut CommitContext<S, M, E>);
+}
+
+#[derive(Debug)]
+pub struct Portal<T, P> {
+    children: T,
+    target: P,
+}
+
+impl<T, P> Portal<T, P> {
+    pub const fn new(children: T, target: P) -> Self {
+        Self { children, target }
+    }
+}
+
+#[derive(Debug)]
+pub struct PortalStorage<T, P> {
+    children: T,
+    target: P,
+    pending_target: Option<P>,
+    is_attached: bool,
+}
+
+impl<T, P> PortalStorage<T, P> {
+    pub fn children(&self) -> &T {
+        &self.children
+    }
+
+    pub fn target(&self) -> &P {
+        &self.target
+    }
+}
+
+impl<T, P, S, M, E> ElementSeq<S, M, E> for Portal<T, P>
+where
+    T: ElementSeq<S, M, E>,
+    P: PortalTarget<T::Storage, S, M, E> + PartialEq,
+{
+    type Storage = PortalStorage<T::Storage, P>;
+
+    fn render_children(self, context: &mut RenderContext<S>) -> Self::Storage {
+        PortalStorage {
+            children: self.children.render_children(context),
+            target: self.target,
+            pending_target: None,
+            is_attached: false,
+        }
+    }
+
+    fn update_children(self, storage: &mut Self::Storage, context: &mut RenderContext<S>) -> bool {
+        let has_changed = self
+            .children
+            .update_children(&mut storage.children, context);
+        if storage.target != self.target {
+            storage.pending_target = Some(self.target);
+            true
+        } else {
+            storage.pending_target = None;
+            has_changed
+        }
+    }
+}
+
+impl<T, P, S, M, E> ViewNodeSeq<S, M, E> for PortalStorage<T, P>
+where
+    T: ViewNodeSeq<S, M, E>,
+    P: PortalTarget<T, S, M, E>,
+{
+    const SIZE_HINT: (usize, Option<usize>) = (0, Some(0));
+
+    fn len(&self) -> usize {
+        0
+    }
+
+    fn commit(&mut self, mode: CommitMode, context: &mut CommitContext<S, M, E>) -> bool {
+        match mode {
+            CommitMode::Mount => {
+                self.children.commit(mode, context);
+                if let Some(target) = self.pending_target.take() {
+                    self.target = target;
+                }
+                self.target.attach(&mut self.children, context);
+                self.is_attached = true;
+            }
+            CommitMode::Update => {
+                let has_changed = self.children.commit(mode, context);
+                match self.pending_target.take() {
+                    Some(target) if self.is_attached => {
+                        self.target.retarget(target, &mut self.children, context);
+                    }
+                    Some(target) => {
+                        self.target = target;
+                        self.target.attach(&mut self.children, context);
+                        self.is_attached = true;
+                    }
+                    None if has_changed => {
+                        self.target.update(&mut self.children, context);
+                    }
+                    None => {}
+                }
+            }
+            CommitMode::Unmount => {
+                if self.is_attached {
+                    self.target.detach(&mut self.children, context);
+                    self.is_attached = false;
+                }
+                self.children.commit(mode, context);
+            }
+        }
+        false
+    }
+
+    fn gc(&mut self) {
+        self.children.gc();
+    }
+}
+
+impl<'context, T, P, S, Visitor> Traversable<Visitor, RenderContext<'context, S>>
+    for PortalStorage<T, P>
+where
+    T: Traversable<Visitor, RenderContext<'context, S>>,
+{
+    fn for_each(&mut self, visitor: &mut Visitor, context: &mut RenderContext<'context, S>) {
+        self.children.for_each(visitor, context);
+    }
+
+    fn for_id(
+        &mut self,
+        id: Id,
+        visitor: &mut Visitor,
+        context: &mut RenderContext<'context, S>,
+    ) -> bool {
+        self.children.for_id(id, visitor, context)
+    }
+}
+
+impl<'context, T, P, S, M, E, Visitor> Traversable<Visitor, CommitContext<'context, S, M, E>>
+    for PortalStorage<T, P>
+where
+    T: Traversable<Visitor, CommitContext<'context, S, M, E>>,
+{
+    fn for_each(&mut self, visitor: &mut Visitor, context: &mut CommitContext<'context, S, M, E>) {
+        // The children are attached by the target, so views reconciling their children skip them.
+        let in_portal = mem::replace(&mut context.in_portal, true);
+        self.children.for_each(visitor, context);
+        context.in_portal = in_portal;
+    }
+
+    fn for_id(
+        &mut self,
+        id: Id,
+        visitor: &mut Visitor,
+        context: &mut CommitContext<'context, S, M, E>,
+    ) -> bool {
+        self.children.for_id(id, visitor, context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::component::HigherOrderComponent;
+    use crate::component_stack::ComponentStack;
+    use crate::element::ViewElement;
+    use crate::event::{EventTarget, Lifecycle};
+    use crate::render_loop::RenderLoop;
+    use crate::test_utils::{Label, NullRuntime, TestState};
+    use crate::view::View;
+    use crate::view_node::{ViewNode, Visitor};
+
+    // The text of the portal label and the name of the portal target.
+    type Message = (&'static str, &'static str);
+
+    type LabelElement = ViewElement<Label, TestState<Message>, Message, ()>;
+
+    struct Container;
+
+    impl View<TestState<Message>, Message, ()> for Container {
+        type Children = (LabelElement, Portal<LabelElement, TestTarget>);
+
+        type State = ();
+
+        fn lifecycle(
+            &self,
+            _lifecycle: Lifecycle<Self>,
+            _view_state: &mut Self::State,
+            children: &mut <Self::Children as ElementSeq<TestState<Message>, Message, ()>>::Storage,
+            context: &mut CommitContext<TestState<Message>, Message, ()>,
+        ) {
+            let mut visitor = CountVisitor::default();
+            children.for_each(&mut visitor, context);
+            context.state().push_log(format!(
+                "reconcile {} portal {}",
+                visitor.attached, visitor.portal
+            ));
+        }
+
+        fn build(
+            &self,
+            _children: &mut <Self::Children as ElementSeq<TestState<Message>, Message, ()>>::Storage,
+            _context: &mut CommitContext<TestState<Message>, Message, ()>,
+        ) -> Self::State {
+        }
+    }
+
+    impl<'event> EventTarget<'event> for Container {
+        type Event = ();
+    }
+
+    #[derive(Default)]
+    struct CountVisitor {
+        attached: usize,
+        portal: usize,
+    }
+
+    impl<'context, V, CS, S, M, E>
+        Visitor<ViewNode<V, CS, S, M, E>, CommitContext<'context, S, M, E>> for CountVisitor
+    where
+        V: View<S, M, E>,
+        CS: ComponentStack<S, M, E, View = V>,
+    {
+        fn visit(
+            &mut self,
+            _node: &mut ViewNode<V, CS, S, M, E>,
+            context: &mut CommitContext<'context, S, M, E>,
+        ) {
+            if context.is_in_portal() {
+                self.portal += 1;
+            } else {
+                self.attached += 1;
+            }
+        }
+    }
+
+    #[derive(PartialEq)]
+    struct TestTarget(&'static str);
+
+    impl<T> PortalTarget<T, TestState<Message>, Message, ()> for TestTarget {
+        fn attach(
+            &mut self,
+            _children: &mut T,
+            context: &mut CommitContext<TestState<Message>, Message, ()>,
+        ) {
+            context.state().push_log(format!("attach {}", self.0));
+        }
+
+        fn update(
+            &mut self,
+            _children: &mut T,
+            context: &mut CommitContext<TestState<Message>, Message, ()>,
+        ) {
+            context.state().push_log(format!("update {}", self.0));
+        }
+
+        fn detach(
+            &mut self,
+            _children: &mut T,
+            context: &mut CommitContext<TestState<Message>, Message, ()>,
+        ) {
+            context.state().push_log(format!("detach {}", self.0));
+        }
+    }
+
+    fn app(
+        _props: &(),
+        context: &mut RenderContext<TestState<Message>>,
+    ) -> ViewElement<Container, TestState<Message>, Message, ()> {
+        let (text, target) = *context.use_atom(|state| &state.value);
+        Container.el((
+            Label::new("main").el(()),
+            Portal::new(Label::new(text).el(()), TestTarget(target)),
+        ))
+    }
+
+    #[test]
+    fn test_portal() {
+        let mut state = TestState::new(("foo", "overlay"));
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec![
+                "mount main",
+                "mount foo",
+                "attach overlay",
+                "reconcile 1 portal 1"
+            ]
+        );
+
+        render_loop.push_message(("bar", "overlay"));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec![
+                "update main",
+                "update bar",
+                "update overlay",
+                "reconcile 1 portal 1"
+            ]
+        );
+
+        render_loop.push_message(("bar", "window"));
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(
+            state.log.take(),
+            vec![
+                "update main",
+                "update bar",
+                "detach overlay",
+                "attach window",
+                "reconcile 1 portal 1"
+            ]
+        );
+    }
+}
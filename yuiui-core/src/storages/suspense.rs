@@ -0,0 +1,508 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use crate::cancellation_token::CancellationToken;
+use crate::command::Command;
+use crate::context::{CommitContext, RenderContext};
+use crate::either::Either;
+use crate::element::ElementSeq;
+use crate::error::RenderError;
+use crate::id::{Id, NodePath};
+use crate::state::Effect;
+use crate::view_node::{CommitMode, Traversable, ViewNodeSeq};
+
+use super::RenderFlags;
+
+type ResourceSlot<T> = Arc<Mutex<Option<Result<T, RenderError>>>>;
+
+#[derive(Debug)]
+pub struct Suspense<K, F, R, P> {
+    key: K,
+    factory: F,
+    render: R,
+    fallback: P,
+}
+
+impl<K, F, R, P> Suspense<K, F, R, P> {
+    pub fn new(key: K, factory: F, render: R, fallback: P) -> Self {
+        Self {
+            key,
+            factory,
+            render,
+            fallback,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SuspenseStorage<T, U, V, K, F, R, P> {
+    active: Either<U, V>,
+    staging: Option<Either<U, V>>,
+    result: Option<Result<T, RenderError>>,
+    slot: Option<ResourceSlot<T>>,
+    cancellation_token: Option<CancellationToken>,
+    needs_fetch: bool,
+    key: K,
+    factory: F,
+    render: R,
+    fallback: P,
+    flags: RenderFlags,
+}
+
+impl<T, U, V, K, F, R, P> SuspenseStorage<T, U, V, K, F, R, P> {
+    pub fn result(&self) -> Option<&Result<T, RenderError>> {
+        self.result.as_ref()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.result.is_none()
+    }
+
+    fn cancel(&mut self) {
+        if let Some(cancellation_token) = self.cancellation_token.take() {
+            cancellation_token.cancel();
+        }
+        self.slot = None;
+    }
+}
+
+impl<T, K, F, Fut, R, P, Content, Fallback, S, M, E> ElementSeq<S, M, E> for Suspense<K, F, R, P>
+where
+    T: Send + 'static,
+    K: PartialEq,
+    F: Fn(&K) -> Fut,
+    Fut: Future<Output = Result<T, RenderError>> + Send + 'static,
+    R: Fn(&T) -> Content,
+    P: Fn(Option<&RenderError>) -> Fallback,
+    Content: ElementSeq<S, M, E>,
+    Fallback: ElementSeq<S, M, E>,
+{
+    type Storage = SuspenseStorage<T, Content::Storage, Fallback::Storage, K, F, R, P>;
+
+    fn render_children(self, context: &mut RenderContext<S>) -> Self::Storage {
+        let node = (self.fallback)(None).render_children(context);
+        SuspenseStorage {
+            active: Either::Right(node),
+            staging: None,
+            result: None,
+            slot: None,
+            cancellation_token: None,
+            needs_fetch: true,
+            key: self.key,
+            factory: self.factory,
+            render: self.render,
+            fallback: self.fallback,
+            flags: RenderFlags::NONE,
+        }
+    }
+
+    fn update_children(self, storage: &mut Self::Storage, context: &mut RenderContext<S>) -> bool {
+        let needs_fetch = storage.key != self.key;
+        storage.key = self.key;
+        storage.factory = self.factory;
+        storage.render = self.render;
+        storage.fallback = self.fallback;
+
+        if needs_fetch {
+            storage.cancel();
+            storage.result = None;
+            storage.needs_fetch = true;
+        }
+
+        let SuspenseStorage {
+            active,
+            staging,
+            result,
+            render,
+            fallback,
+            flags,
+            ..
+        } = storage;
+        let current = if flags.contains(RenderFlags::SWAPPED) {
+            staging.as_mut().unwrap()
+        } else {
+            &mut *active
+        };
+
+        match (current, result.as_ref()) {
+            (Either::Left(node), Some(Ok(value))) => {
+                let has_changed = render(value).update_children(node, context);
+                mark_updated(flags, has_changed)
+            }
+            (Either::Right(node), Some(Err(error))) => {
+                let has_changed = fallback(Some(error)).update_children(node, context);
+                mark_updated(flags, has_changed)
+            }
+            (Either::Right(node), None) => {
+                let has_changed = fallback(None).update_children(node, context);
+                mark_updated(flags, has_changed)
+            }
+            (_, result) => {
+                let node = match result {
+                    Some(Ok(value)) => Either::Left(render(value).render_children(context)),
+                    Some(Err(error)) => {
+                        Either::Right(fallback(Some(error)).render_children(context))
+                    }
+                    None => Either::Right(fallback(None).render_children(context)),
+                };
+                *staging = Some(node);
+                *flags |= RenderFlags::SWAPPED;
+                true
+            }
+        }
+    }
+}
+
+impl<T, U, V, K, F, Fut, R, P, Content, Fallback, S, M, E> ViewNodeSeq<S, M, E>
+    for SuspenseStorage<T, U, V, K, F, R, P>
+where
+    T: Send + 'static,
+    U: ViewNodeSeq<S, M, E>,
+    V: ViewNodeSeq<S, M, E>,
+    F: Fn(&K) -> Fut,
+    Fut: Future<Output = Result<T, RenderError>> + Send + 'static,
+    R: Fn(&T) -> Content,
+    P: Fn(Option<&RenderError>) -> Fallback,
+    Content: ElementSeq<S, M, E, Storage = U>,
+    Fallback: ElementSeq<S, M, E, Storage = V>,
+{
+    const SIZE_HINT: (usize, Option<usize>) = {
+        let (content_lower, content_upper) = U::SIZE_HINT;
+        let (fallback_lower, fallback_upper) = V::SIZE_HINT;
+        let lower = if content_lower < fallback_lower {
+            content_lower
+        } else {
+            fallback_lower
+        };
+        let upper = match (content_upper, fallback_upper) {
+            (Some(x), Some(y)) if x < y => Some(y),
+            (Some(x), Some(_)) => Some(x),
+            _ => None,
+        };
+        (lower, upper)
+    };
+
+    fn len(&self) -> usize {
+        match &self.active {
+            Either::Left(node) => node.len(),
+            Either::Right(node) => node.len(),
+        }
+    }
+
+    fn commit(&mut self, mode: CommitMode, context: &mut CommitContext<S, M, E>) -> bool {
+        let mut result = false;
+        if mode == CommitMode::Unmount && self.cancellation_token.is_some() {
+            self.cancel();
+            self.needs_fetch = self.result.is_none();
+        }
+        if self.flags.contains(RenderFlags::SWAPPED) {
+            if self.flags.contains(RenderFlags::COMMITED) {
+                result |= commit_either(&mut self.active, CommitMode::Unmount, context);
+            }
+            self.active = self.staging.take().unwrap();
+            if mode != CommitMode::Unmount {
+                result |= commit_either(&mut self.active, CommitMode::Mount, context);
+            }
+        } else if self.flags.contains(RenderFlags::UPDATED) || mode.is_propagable() {
+            result |= commit_either(&mut self.active, mode, context);
+        }
+        if mode != CommitMode::Unmount {
+            result |= self.resolve(context);
+            if self.needs_fetch {
+                self.fetch(context);
+            }
+        }
+        self.flags = RenderFlags::COMMITED;
+        result
+    }
+
+    fn gc(&mut self) {
+        match &mut self.active {
+            Either::Left(node) => node.gc(),
+            Either::Right(node) => node.gc(),
+        }
+        if !self.flags.contains(RenderFlags::SWAPPED) {
+            self.staging = None;
+        }
+    }
+}
+
+impl<T, U, V, K, F, R, P> SuspenseStorage<T, U, V, K, F, R, P> {
+    fn fetch<Fut, S, M, E>(&mut self, context: &mut CommitContext<S, M, E>)
+    where
+        T: Send + 'static,
+        F: Fn(&K) -> Fut,
+        Fut: Future<Output = Result<T, RenderError>> + Send + 'static,
+    {
+        let slot: ResourceSlot<T> = Arc::new(Mutex::new(None));
+        let cancellation_token = CancellationToken::new();
+        let future = (self.factory)(&self.key);
+        let node_path = NodePath {
+            id_path: context.id_path().to_vec(),
+            level: 0,
+        };
+        let command = Command::from_future({
+            let slot = slot.clone();
+            async move {
+                let result = future.await;
+                *slot.lock().unwrap() = Some(result);
+                Effect::Update(vec![node_path])
+            }
+        });
+        context.spawn_effect(command, Some(cancellation_token.clone()));
+        self.slot = Some(slot);
+        self.cancellation_token = Some(cancellation_token);
+        self.needs_fetch = false;
+    }
+
+    fn resolve<Content, Fallback, S, M, E>(&mut self, context: &mut CommitContext<S, M, E>) -> bool
+    where
+        U: ViewNodeSeq<S, M, E>,
+        V: ViewNodeSeq<S, M, E>,
+        R: Fn(&T) -> Content,
+        P: Fn(Option<&RenderError>) -> Fallback,
+        Content: ElementSeq<S, M, E, Storage = U>,
+        Fallback: ElementSeq<S, M, E, Storage = V>,
+    {
+        let result = match self
+            .slot
+            .as_ref()
+            .and_then(|slot| slot.lock().unwrap().take())
+        {
+            Some(result) => self.result.insert(result),
+            None => return false,
+        };
+        self.slot = None;
+        self.cancellation_token = None;
+
+        let mut render_context = context.render_context();
+        let node = match (&mut self.active, &*result) {
+            (Either::Right(node), Err(error)) => {
+                if (self.fallback)(Some(error)).update_children(node, &mut render_context) {
+                    node.commit(CommitMode::Update, context);
+                }
+                return true;
+            }
+            (_, Ok(value)) => {
+                Either::Left((self.render)(value).render_children(&mut render_context))
+            }
+            (_, Err(error)) => {
+                Either::Right((self.fallback)(Some(error)).render_children(&mut render_context))
+            }
+        };
+
+        commit_either(&mut self.active, CommitMode::Unmount, context);
+        self.active = node;
+        commit_either(&mut self.active, CommitMode::Mount, context);
+        true
+    }
+}
+
+impl<T, U, V, K, F, R, P, Visitor, Context> Traversable<Visitor, Context>
+    for SuspenseStorage<T, U, V, K, F, R, P>
+where
+    U: Traversable<Visitor, Context>,
+    V: Traversable<Visitor, Context>,
+{
+    fn for_each(&mut self, visitor: &mut Visitor, context: &mut Context) {
+        match &mut self.active {
+            Either::Left(node) => node.for_each(visitor, context),
+            Either::Right(node) => node.for_each(visitor, context),
+        }
+    }
+
+    fn for_id(&mut self, id: Id, visitor: &mut Visitor, context: &mut Context) -> bool {
+        match &mut self.active {
+            Either::Left(node) => node.for_id(id, visitor, context),
+            Either::Right(node) => node.for_id(id, visitor, context),
+        }
+    }
+}
+
+fn commit_either<U, V, S, M, E>(
+    node: &mut Either<U, V>,
+    mode: CommitMode,
+    context: &mut CommitContext<S, M, E>,
+) -> bool
+where
+    U: ViewNodeSeq<S, M, E>,
+    V: ViewNodeSeq<S, M, E>,
+{
+    match node {
+        Either::Left(node) => node.commit(mode, context),
+        Either::Right(node) => node.commit(mode, context),
+    }
+}
+
+fn mark_updated(flags: &mut RenderFlags, has_changed: bool) -> bool {
+    if flags.contains(RenderFlags::SWAPPED) {
+        true
+    } else {
+        if has_changed {
+            *flags |= RenderFlags::UPDATED;
+        }
+        has_changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor;
+    use futures::future::{self, Ready};
+    use std::cell::RefCell;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::command::CommandRuntime;
+    use crate::component::HigherOrderComponent;
+    use crate::element::ViewElement;
+    use crate::render_loop::RenderLoop;
+    use crate::test_utils::{counting_token, Container, Label, TestState};
+    use crate::view::View;
+
+    type LabelElement = ViewElement<Label, TestState<u32>, u32, ()>;
+
+    type FactoryFn = fn(&u32) -> Ready<Result<u32, RenderError>>;
+
+    type RenderFn = fn(&u32) -> LabelElement;
+
+    type FallbackFn = fn(Option<&RenderError>) -> LabelElement;
+
+    type AppContainer = Container<Suspense<u32, FactoryFn, RenderFn, FallbackFn>>;
+
+    type OptionalContainer = Container<Option<ViewElement<AppContainer, TestState<u32>, u32, ()>>>;
+
+    #[derive(Default)]
+    struct TestRuntime {
+        effects: RefCell<Vec<Command<Effect>>>,
+        canceled: Arc<AtomicUsize>,
+    }
+
+    impl TestRuntime {
+        fn resolve(&self, index: usize) -> Effect {
+            match self.effects.borrow_mut().remove(index) {
+                Command::Future(future) => executor::block_on(future),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    impl CommandRuntime<u32> for TestRuntime {
+        fn spawn_command(
+            &self,
+            _command: Command<u32>,
+            _cancellation_token: Option<CancellationToken>,
+        ) {
+        }
+
+        fn spawn_effect(
+            &self,
+            command: Command<Effect>,
+            cancellation_token: Option<CancellationToken>,
+        ) {
+            self.effects.borrow_mut().push(command);
+            if let Some(cancellation_token) = cancellation_token {
+                cancellation_token.register(counting_token(self.canceled.clone()));
+            }
+        }
+    }
+
+    fn factory(key: &u32) -> Ready<Result<u32, RenderError>> {
+        if key % 2 == 1 {
+            future::ready(Err(RenderError::new(format!("odd {}", key))))
+        } else {
+            future::ready(Ok(key * 10))
+        }
+    }
+
+    fn render(value: &u32) -> LabelElement {
+        Label(format!("value {}", value)).el(())
+    }
+
+    fn fallback(error: Option<&RenderError>) -> LabelElement {
+        match error {
+            Some(error) => Label(format!("error: {}", error)).el(()),
+            None => Label::new("loading").el(()),
+        }
+    }
+
+    fn app(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<AppContainer, TestState<u32>, u32, ()> {
+        let key = *context.use_atom(|state| &state.value);
+        Container::new().el(Suspense::new(
+            key,
+            factory as FactoryFn,
+            render as RenderFn,
+            fallback as FallbackFn,
+        ))
+    }
+
+    #[test]
+    fn test_suspense() {
+        let mut state = TestState::new(2);
+        let runtime = TestRuntime::default();
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert_eq!(state.log.take(), vec!["mount loading"]);
+
+        render_loop.push_effect(runtime.resolve(0));
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert_eq!(state.log.take(), vec!["unmount loading", "mount value 20"]);
+
+        render_loop.push_message(4);
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert_eq!(state.log.take(), vec!["unmount value 20", "mount loading"]);
+
+        render_loop.push_message(5);
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert_eq!(state.log.take(), vec!["update loading"]);
+        assert_eq!(runtime.canceled.load(Ordering::Relaxed), 1);
+
+        render_loop.push_effect(runtime.resolve(0));
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert!(state.log.take().is_empty());
+
+        render_loop.push_effect(runtime.resolve(0));
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert_eq!(state.log.take(), vec!["update error: odd 5"]);
+    }
+
+    #[test]
+    fn test_resolve_after_unmount() {
+        fn optional_app(
+            _props: &(),
+            context: &mut RenderContext<TestState<u32>>,
+        ) -> ViewElement<OptionalContainer, TestState<u32>, u32, ()> {
+            let key = *context.use_atom(|state| &state.value);
+            Container::new().el((key > 0).then(|| {
+                Container::new().el(Suspense::new(
+                    key,
+                    factory as FactoryFn,
+                    render as RenderFn,
+                    fallback as FallbackFn,
+                ))
+            }))
+        }
+
+        let mut state = TestState::new(2);
+        let runtime = TestRuntime::default();
+        let mut render_loop = RenderLoop::create(optional_app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert_eq!(state.log.take(), vec!["mount loading"]);
+
+        render_loop.push_message(0);
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert_eq!(state.log.take(), vec!["unmount loading"]);
+        assert_eq!(runtime.canceled.load(Ordering::Relaxed), 1);
+
+        // The resource may still resolve if it raced with the cancellation.
+        render_loop.push_effect(runtime.resolve(0));
+        assert_eq!(render_loop.pending_work().nodes_to_update, 0);
+        render_loop.run_forever(&mut state, &(), &runtime);
+        assert!(state.log.take().is_empty());
+    }
+}
@@ -6,9 +6,7 @@ use crate::view_node::{CommitMode, Traversable, ViewNodeSeq};
 impl<S, M, E> ElementSeq<S, M, E> for () {
     type Storage = ();
 
-    fn render_children(self, _context: &mut RenderContext<S>) -> Self::Storage {
-        ()
-    }
+    fn render_children(self, _context: &mut RenderContext<S>) -> Self::Storage {}
 
     fn update_children(self, _nodes: &mut Self::Storage, _context: &mut RenderContext<S>) -> bool {
         false
@@ -67,7 +65,7 @@ macro_rules! define_tuple_impl {
                 storage: &mut Self::Storage,
                 context: &mut RenderContext<S>,
             ) -> bool {
-                $(self.$n.update_children(&mut storage.$n, context))||*
+                $(self.$n.update_children(&mut storage.$n, context))|*
             }
         }
 
@@ -96,7 +94,7 @@ macro_rules! define_tuple_impl {
                 mode: CommitMode,
                 context: &mut CommitContext<S, M, E>,
             ) -> bool {
-                $(self.$n.commit(mode, context))||*
+                $(self.$n.commit(mode, context))|*
             }
 
             fn gc(&mut self) {
@@ -140,3 +138,42 @@ define_tuple_impls! {
     T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11;
     0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::component::HigherOrderComponent;
+    use crate::context::RenderContext;
+    use crate::element::ViewElement;
+    use crate::render_loop::RenderLoop;
+    use crate::test_utils::{Container, Label, NullRuntime, TestState};
+    use crate::view::View;
+
+    type LabelElement = ViewElement<Label, TestState<u32>, u32, ()>;
+
+    type AppContainer = Container<(LabelElement, LabelElement)>;
+
+    fn app(
+        _props: &(),
+        context: &mut RenderContext<TestState<u32>>,
+    ) -> ViewElement<AppContainer, TestState<u32>, u32, ()> {
+        let value = *context.use_atom(|state| &state.value);
+        Container::new().el((
+            Label(format!("first {}", value)).el(()),
+            Label(format!("second {}", value)).el(()),
+        ))
+    }
+
+    #[test]
+    fn test_update_every_element() {
+        let mut state = TestState::new(0);
+        let mut render_loop = RenderLoop::create(app.el(()), &state);
+
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["mount first 0", "mount second 0"]);
+
+        // The first change must not short-circuit the update and the commit of the rest.
+        render_loop.push_message(1);
+        render_loop.run_forever(&mut state, &(), &NullRuntime);
+        assert_eq!(state.log.take(), vec!["update first 1", "update second 1"]);
+    }
+}
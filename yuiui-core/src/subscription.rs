@@ -0,0 +1,151 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+
+use crate::id::{Id, IdPath, Level, NodePath};
+
+// Shared with the registry through a weak reference. A mutex keeps atoms `Send`.
+#[derive(Debug, Default)]
+pub(crate) struct Subscribers(Arc<Mutex<Vec<NodePath>>>);
+
+impl Subscribers {
+    pub(crate) fn lock(&self) -> MutexGuard<'_, Vec<NodePath>> {
+        self.0.lock().unwrap()
+    }
+}
+
+type WeakSubscribers = Weak<Mutex<Vec<NodePath>>>;
+
+#[derive(Default)]
+pub(crate) struct SubscriptionRegistry {
+    entries: RefCell<HashMap<Id, Entry>>,
+    live_nodes: RefCell<HashSet<Id>>,
+    staged_nodes: RefCell<HashSet<Id>>,
+}
+
+impl SubscriptionRegistry {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn subscribe(&self, subscribers: &Subscribers, id_path: &IdPath, level: Level) {
+        let id = Id::from(id_path);
+        let mut entries = self.entries.borrow_mut();
+        let entry = entries.entry(id).or_insert_with(|| Entry {
+            node: NodePath {
+                id_path: id_path.to_vec(),
+                level,
+            },
+            subscribers: Vec::new(),
+        });
+        if entry.node.level < level {
+            entry.node.level = level;
+        }
+
+        let mut nodes = subscribers.lock();
+        if let Some(node) = nodes.iter_mut().find(|node| node.id_path == id_path) {
+            if node.level < level {
+                node.level = level;
+            }
+            return;
+        }
+        nodes.push(NodePath {
+            id_path: id_path.to_vec(),
+            level,
+        });
+
+        let weak = Arc::downgrade(&subscribers.0);
+        if !entry.subscribers.iter().any(|other| other.ptr_eq(&weak)) {
+            entry.subscribers.retain(|other| other.strong_count() > 0);
+            entry.subscribers.push(weak);
+        }
+    }
+
+    // The nodes are kept apart from the atoms, so they outlive a state that has been replaced.
+    pub(crate) fn subscribed_nodes(&self) -> Vec<NodePath> {
+        self.entries
+            .borrow()
+            .values()
+            .map(|entry| entry.node.clone())
+            .collect()
+    }
+
+    // Live nodes are left alone, since they are never mounted again to leave the staging set.
+    pub(crate) fn stage(&self, id: Id) {
+        if !self.live_nodes.borrow().contains(&id) {
+            self.staged_nodes.borrow_mut().insert(id);
+        }
+    }
+
+    pub(crate) fn mount(&self, id: Id) -> bool {
+        self.live_nodes.borrow_mut().insert(id);
+        self.staged_nodes.borrow_mut().remove(&id)
+    }
+
+    pub(crate) fn unmount(&self, id: Id) {
+        self.staged_nodes.borrow_mut().remove(&id);
+        self.live_nodes.borrow_mut().remove(&id);
+        if let Some(entry) = self.entries.borrow_mut().remove(&id) {
+            for subscribers in entry.subscribers.iter().filter_map(Weak::upgrade) {
+                subscribers
+                    .lock()
+                    .unwrap()
+                    .retain(|node| Id::from(node.id_path.as_slice()) != id);
+            }
+        }
+    }
+
+    // Once every lane has been committed, the nodes that are still staged were rendered
+    // and then discarded without ever being mounted.
+    pub(crate) fn discard_staged(&self) {
+        let staged_nodes = mem::take(&mut *self.staged_nodes.borrow_mut());
+        for id in staged_nodes {
+            self.unmount(id);
+        }
+    }
+
+    pub(crate) fn is_mounted(&self, id: Id) -> bool {
+        self.live_nodes.borrow().contains(&id)
+    }
+
+    pub(crate) fn is_alive(&self, id_path: &IdPath) -> bool {
+        let id = Id::from(id_path);
+        id.is_root()
+            || self.live_nodes.borrow().contains(&id)
+            || self.staged_nodes.borrow().contains(&id)
+    }
+
+    pub(crate) fn subscription_count(&self, id: Id) -> usize {
+        self.entries.borrow().get(&id).map_or(0, |entry| {
+            entry
+                .subscribers
+                .iter()
+                .filter_map(Weak::upgrade)
+                .filter(|subscribers| {
+                    subscribers
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .any(|node| Id::from(node.id_path.as_slice()) == id)
+                })
+                .count()
+        })
+    }
+}
+
+struct Entry {
+    node: NodePath,
+    subscribers: Vec<WeakSubscribers>,
+}
+
+impl fmt::Debug for SubscriptionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SubscriptionRegistry")
+            .field("entries", &self.entries.borrow().keys())
+            .field("live_nodes", &self.live_nodes.borrow())
+            .field("staged_nodes", &self.staged_nodes.borrow())
+            .finish()
+    }
+}
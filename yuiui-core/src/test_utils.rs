@@ -0,0 +1,150 @@
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::cancellation_token::{CancellationToken, RawToken, RawTokenVTable};
+use crate::command::{Command, CommandRuntime, Commands};
+use crate::context::CommitContext;
+use crate::element::ElementSeq;
+use crate::event::{EventTarget, Lifecycle};
+use crate::inspector::InspectDebug;
+use crate::state::{Atom, Effect, State};
+use crate::view::View;
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+pub(crate) struct TestState<T> {
+    pub(crate) value: Atom<T>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) log: RefCell<Vec<String>>,
+}
+
+impl<T> TestState<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self {
+            value: Atom::new(value),
+            log: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub(crate) fn push_log(&self, entry: impl Into<String>) {
+        self.log.borrow_mut().push(entry.into());
+    }
+}
+
+impl<T> State for TestState<T> {
+    type Message = T;
+
+    fn update(&mut self, value: Self::Message, _commands: &mut Commands<Self::Message>) -> Effect {
+        self.value.set(value)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Label(pub(crate) String);
+
+impl Label {
+    pub(crate) fn new(text: impl Into<String>) -> Self {
+        Self(text.into())
+    }
+}
+
+impl<T> View<TestState<T>, T, ()> for Label {
+    type Children = ();
+
+    type State = ();
+
+    fn lifecycle(
+        &self,
+        lifecycle: Lifecycle<Self>,
+        _view_state: &mut Self::State,
+        _children: &mut <Self::Children as ElementSeq<TestState<T>, T, ()>>::Storage,
+        context: &mut CommitContext<TestState<T>, T, ()>,
+    ) {
+        context
+            .state()
+            .push_log(format!("{} {}", lifecycle_name(&lifecycle), self.0));
+    }
+
+    fn build(
+        &self,
+        _children: &mut <Self::Children as ElementSeq<TestState<T>, T, ()>>::Storage,
+        _context: &mut CommitContext<TestState<T>, T, ()>,
+    ) -> Self::State {
+    }
+
+    fn inspect(&self) -> Option<String> {
+        self.inspect_debug()
+    }
+}
+
+impl<'event> EventTarget<'event> for Label {
+    type Event = ();
+}
+
+pub(crate) struct Container<Children>(PhantomData<Children>);
+
+impl<Children> Container<Children> {
+    pub(crate) fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<Children, S, M, E> View<S, M, E> for Container<Children>
+where
+    Children: ElementSeq<S, M, E>,
+{
+    type Children = Children;
+
+    type State = ();
+
+    fn build(
+        &self,
+        _children: &mut <Self::Children as ElementSeq<S, M, E>>::Storage,
+        _context: &mut CommitContext<S, M, E>,
+    ) -> Self::State {
+    }
+}
+
+impl<'event, Children> EventTarget<'event> for Container<Children> {
+    type Event = ();
+}
+
+pub(crate) struct NullRuntime;
+
+impl<M> CommandRuntime<M> for NullRuntime {
+    fn spawn_command(&self, _command: Command<M>, _cancellation_token: Option<CancellationToken>) {}
+
+    fn spawn_effect(
+        &self,
+        _command: Command<Effect>,
+        _cancellation_token: Option<CancellationToken>,
+    ) {
+    }
+}
+
+pub(crate) fn lifecycle_name<V>(lifecycle: &Lifecycle<V>) -> &'static str {
+    match lifecycle {
+        Lifecycle::Mount => "mount",
+        Lifecycle::Remount => "remount",
+        Lifecycle::Update(_) => "update",
+        Lifecycle::Unmount => "unmount",
+    }
+}
+
+pub(crate) fn counting_token(canceled: Arc<AtomicUsize>) -> RawToken {
+    static VTABLE: RawTokenVTable = RawTokenVTable::new(cancel, drop);
+
+    unsafe fn cancel(data: *const ()) {
+        Box::from_raw(data as *mut Arc<AtomicUsize>).fetch_add(1, Ordering::Relaxed);
+    }
+
+    unsafe fn drop(data: *const ()) {
+        let _ = Box::from_raw(data as *mut Arc<AtomicUsize>);
+    }
+
+    let data = Box::into_raw(Box::new(canceled)) as *const ();
+
+    RawToken::new(data, &VTABLE)
+}
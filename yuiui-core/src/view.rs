@@ -33,6 +33,11 @@ pub trait View<S, M, E>: Sized + for<'event> EventTarget<'event> {
         context: &mut CommitContext<S, M, E>,
     ) -> Self::State;
 
+    #[inline]
+    fn inspect(&self) -> Option<String> {
+        None
+    }
+
     #[inline]
     fn el(self, children: Self::Children) -> ViewElement<Self, S, M, E> {
         ViewElement::new(self, children)
@@ -1,4 +1,5 @@
 mod commit_subtree_visitor;
+mod inspect_visitor;
 mod multicast_event_visitor;
 mod unicast_event_visitor;
 mod update_subtree_visitor;
@@ -8,15 +9,18 @@ use std::{fmt, mem};
 
 use crate::component_stack::ComponentStack;
 use crate::context::{CommitContext, RenderContext};
+use crate::deadline::Deadline;
 use crate::element::ElementSeq;
 use crate::event::Lifecycle;
-use crate::id::{Id, IdPath, IdPathBuf, IdTree, Level};
+use crate::id::{Id, IdPath, IdPathBuf, IdTree, Level, NodePath};
+use crate::local_state::LocalSlots;
 use crate::view::View;
 
-use commit_subtree_visitor::CommitSubtreeVisitor;
-use multicast_event_visitor::MulticastEventVisitor;
-use unicast_event_visitor::UnicastEventVisitor;
-use update_subtree_visitor::UpdateSubtreeVisitor;
+pub(crate) use commit_subtree_visitor::CommitSubtreeVisitor;
+pub(crate) use inspect_visitor::InspectVisitor;
+pub(crate) use multicast_event_visitor::MulticastEventVisitor;
+pub(crate) use unicast_event_visitor::UnicastEventVisitor;
+pub(crate) use update_subtree_visitor::UpdateSubtreeVisitor;
 
 pub struct ViewNode<V: View<S, M, E>, CS: ComponentStack<S, M, E, View = V>, S, M, E> {
     pub(crate) id: Id,
@@ -25,6 +29,7 @@ pub struct ViewNode<V: View<S, M, E>, CS: ComponentStack<S, M, E, View = V>, S,
     pub(crate) view_state: Option<V::State>,
     pub(crate) children: <V::Children as ElementSeq<S, M, E>>::Storage,
     pub(crate) components: CS,
+    pub(crate) local_states: Vec<LocalSlots>,
     pub(crate) dirty: bool,
 }
 
@@ -46,6 +51,7 @@ where
             view_state: None,
             children,
             components,
+            local_states: Vec::new(),
             dirty: true,
         }
     }
@@ -71,13 +77,17 @@ where
 
         let mut result = match mode {
             CommitMode::Mount | CommitMode::Update => self.children.commit(mode, context),
-            CommitMode::Unmount => false,
+            CommitMode::Unmount => {
+                CS::commit(&mut self.into(), mode, context);
+                false
+            }
         };
 
         result |= match (mode, self.pending_view.take(), self.view_state.as_mut()) {
             (CommitMode::Mount, None, None) => {
                 let mut view_state = self.view.build(&mut self.children, context);
-                self.view.lifecycle(
+                invoke_lifecycle(
+                    &self.view,
                     Lifecycle::Mount,
                     &mut view_state,
                     &mut self.children,
@@ -88,7 +98,8 @@ where
             }
             (CommitMode::Mount, Some(pending_view), None) => {
                 let mut view_state = pending_view.build(&mut self.children, context);
-                pending_view.lifecycle(
+                invoke_lifecycle(
+                    &pending_view,
                     Lifecycle::Mount,
                     &mut view_state,
                     &mut self.children,
@@ -98,15 +109,26 @@ where
                 true
             }
             (CommitMode::Mount, None, Some(view_state)) => {
-                self.view
-                    .lifecycle(Lifecycle::Remount, view_state, &mut self.children, context);
+                invoke_lifecycle(
+                    &self.view,
+                    Lifecycle::Remount,
+                    view_state,
+                    &mut self.children,
+                    context,
+                );
                 true
             }
             (CommitMode::Mount, Some(pending_view), Some(view_state)) => {
-                self.view
-                    .lifecycle(Lifecycle::Remount, view_state, &mut self.children, context);
+                invoke_lifecycle(
+                    &self.view,
+                    Lifecycle::Remount,
+                    view_state,
+                    &mut self.children,
+                    context,
+                );
                 let old_view = mem::replace(&mut self.view, pending_view);
-                self.view.lifecycle(
+                invoke_lifecycle(
+                    &self.view,
                     Lifecycle::Update(old_view),
                     view_state,
                     &mut self.children,
@@ -121,7 +143,8 @@ where
             }
             (CommitMode::Update, Some(pending_view), Some(view_state)) => {
                 let old_view = mem::replace(&mut self.view, pending_view);
-                self.view.lifecycle(
+                invoke_lifecycle(
+                    &self.view,
                     Lifecycle::Update(old_view),
                     view_state,
                     &mut self.children,
@@ -131,13 +154,23 @@ where
             }
             (CommitMode::Unmount, None, None) => false,
             (CommitMode::Unmount, None, Some(view_state)) => {
-                self.view
-                    .lifecycle(Lifecycle::Unmount, view_state, &mut self.children, context);
+                invoke_lifecycle(
+                    &self.view,
+                    Lifecycle::Unmount,
+                    view_state,
+                    &mut self.children,
+                    context,
+                );
                 true
             }
             (CommitMode::Unmount, Some(pending_view), Some(view_state)) => {
-                self.view
-                    .lifecycle(Lifecycle::Unmount, view_state, &mut self.children, context);
+                invoke_lifecycle(
+                    &self.view,
+                    Lifecycle::Unmount,
+                    view_state,
+                    &mut self.children,
+                    context,
+                );
                 self.pending_view = Some(pending_view);
                 true
             }
@@ -149,20 +182,51 @@ where
         self.dirty = false;
 
         result |= match mode {
-            CommitMode::Mount | CommitMode::Update => false,
+            CommitMode::Mount | CommitMode::Update => {
+                CS::commit(&mut self.into(), mode, context);
+                false
+            }
             CommitMode::Unmount => self.children.commit(mode, context),
         };
 
+        match mode {
+            CommitMode::Mount => {
+                // Nodes that are not staged lost their subscriptions when they were unmounted
+                // or discarded and have not been rendered since, so they have to be rendered
+                // again.
+                if !context.subscriptions.mount(self.id) {
+                    Self::resubscribe(context);
+                }
+            }
+            CommitMode::Update => {}
+            CommitMode::Unmount => {
+                context.providers.remove(self.id);
+                context.providers.unsubscribe(context.id_stack.id_path());
+                context.subscriptions.unmount(self.id);
+            }
+        }
+
         result
     }
 
+    fn resubscribe(context: &mut CommitContext<S, M, E>) {
+        if CS::LEVEL > 0 {
+            context.pending_updates.push(NodePath {
+                id_path: context.id_stack.id_path().to_vec(),
+                level: CS::LEVEL,
+            });
+        }
+    }
+
     pub(crate) fn commit_subtree(
         &mut self,
         id_tree: &IdTree<()>,
         context: &mut CommitContext<S, M, E>,
-    ) {
-        let mut visitor = CommitSubtreeVisitor::new(CommitMode::Update, id_tree.root());
+        deadline: &dyn Deadline,
+    ) -> Vec<IdPathBuf> {
+        let mut visitor = CommitSubtreeVisitor::new(CommitMode::Update, id_tree.root(), deadline);
         visitor.visit(self, context);
+        visitor.into_deferred()
     }
 
     pub(crate) fn dispatch_unicast_event(
@@ -216,6 +280,32 @@ where
     }
 }
 
+fn invoke_lifecycle<V, S, M, E>(
+    view: &V,
+    lifecycle: Lifecycle<V>,
+    view_state: &mut V::State,
+    children: &mut <V::Children as ElementSeq<S, M, E>>::Storage,
+    context: &mut CommitContext<S, M, E>,
+) where
+    V: View<S, M, E>,
+{
+    context.record_lifecycle(&lifecycle);
+    #[cfg(feature = "tracing")]
+    let _tracing_span = tracing::trace_span!(
+        "lifecycle",
+        view = std::any::type_name::<V>(),
+        lifecycle = match &lifecycle {
+            Lifecycle::Mount => "mount",
+            Lifecycle::Remount => "remount",
+            Lifecycle::Update(_) => "update",
+            Lifecycle::Unmount => "unmount",
+        },
+        id_path = ?context.id_path()
+    )
+    .entered();
+    view.lifecycle(lifecycle, view_state, children, context);
+}
+
 impl<V, CS, S, M, E> fmt::Debug for ViewNode<V, CS, S, M, E>
 where
     V: View<S, M, E> + fmt::Debug,
@@ -243,6 +333,7 @@ pub struct ViewNodeMut<'a, V: View<S, M, E>, CS: ?Sized, S, M, E> {
     pub(crate) view_state: &'a mut Option<V::State>,
     pub(crate) children: &'a mut <V::Children as ElementSeq<S, M, E>>::Storage,
     pub(crate) components: &'a mut CS,
+    pub(crate) local_states: &'a mut Vec<LocalSlots>,
     pub(crate) dirty: &'a mut bool,
 }
 
@@ -281,17 +372,31 @@ where
             view_state: &mut node.view_state,
             children: &mut node.children,
             components: &mut node.components,
+            local_states: &mut node.local_states,
             dirty: &mut node.dirty,
         }
     }
 }
 
-pub trait ViewNodeSeq<S, M, E>:
+pub trait TraversableSeq<S, M, E>:
     for<'a, 'context> Traversable<MulticastEventVisitor<'a>, CommitContext<'context, S, M, E>>
     + for<'a, 'context> Traversable<CommitSubtreeVisitor<'a>, CommitContext<'context, S, M, E>>
     + for<'a, 'context> Traversable<UnicastEventVisitor<'a>, CommitContext<'context, S, M, E>>
     + for<'a, 'context> Traversable<UpdateSubtreeVisitor<'a>, RenderContext<'context, S>>
+    + for<'context> Traversable<InspectVisitor, RenderContext<'context, S>>
 {
+}
+
+impl<T, S, M, E> TraversableSeq<S, M, E> for T where
+    T: for<'a, 'context> Traversable<MulticastEventVisitor<'a>, CommitContext<'context, S, M, E>>
+        + for<'a, 'context> Traversable<CommitSubtreeVisitor<'a>, CommitContext<'context, S, M, E>>
+        + for<'a, 'context> Traversable<UnicastEventVisitor<'a>, CommitContext<'context, S, M, E>>
+        + for<'a, 'context> Traversable<UpdateSubtreeVisitor<'a>, RenderContext<'context, S>>
+        + for<'context> Traversable<InspectVisitor, RenderContext<'context, S>>
+{
+}
+
+pub trait ViewNodeSeq<S, M, E>: TraversableSeq<S, M, E> {
     const SIZE_HINT: (usize, Option<usize>);
 
     const IS_STATIC: bool = {
@@ -303,6 +408,10 @@ pub trait ViewNodeSeq<S, M, E>:
 
     fn len(&self) -> usize;
 
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
     fn commit(&mut self, mode: CommitMode, context: &mut CommitContext<S, M, E>) -> bool;
 
     fn gc(&mut self);
@@ -348,9 +457,8 @@ where
 {
     fn for_each(&mut self, visitor: &mut Visitor, context: &mut RenderContext<'context, S>) {
         context.id_stack.push(self.id);
-        let result = visitor.visit(self, context);
+        visitor.visit(self, context);
         context.id_stack.pop();
-        result
     }
 
     fn for_id(
@@ -380,9 +488,8 @@ where
 {
     fn for_each(&mut self, visitor: &mut Visitor, context: &mut CommitContext<'context, S, M, E>) {
         context.id_stack.push(self.id);
-        let result = visitor.visit(self, context);
+        visitor.visit(self, context);
         context.id_stack.pop();
-        result
     }
 
     fn for_id(
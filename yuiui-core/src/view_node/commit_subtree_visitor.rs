@@ -1,6 +1,7 @@
 use crate::component_stack::ComponentStack;
 use crate::context::CommitContext;
-use crate::id::id_tree;
+use crate::deadline::Deadline;
+use crate::id::{id_tree, IdPathBuf};
 use crate::view::View;
 
 use super::{CommitMode, Traversable, ViewNode, Visitor};
@@ -8,11 +9,28 @@ use super::{CommitMode, Traversable, ViewNode, Visitor};
 pub struct CommitSubtreeVisitor<'a> {
     mode: CommitMode,
     cursor: id_tree::Cursor<'a, ()>,
+    deadline: &'a dyn Deadline,
+    deferred: Vec<IdPathBuf>,
+    has_visited: bool,
 }
 
 impl<'a> CommitSubtreeVisitor<'a> {
-    pub fn new(mode: CommitMode, cursor: id_tree::Cursor<'a, ()>) -> Self {
-        Self { mode, cursor }
+    pub fn new(
+        mode: CommitMode,
+        cursor: id_tree::Cursor<'a, ()>,
+        deadline: &'a dyn Deadline,
+    ) -> Self {
+        Self {
+            mode,
+            cursor,
+            deadline,
+            deferred: Vec::new(),
+            has_visited: false,
+        }
+    }
+
+    pub fn into_deferred(self) -> Vec<IdPathBuf> {
+        self.deferred
     }
 }
 
@@ -27,14 +45,45 @@ where
         node: &mut ViewNode<V, CS, S, M, E>,
         context: &mut CommitContext<'context, S, M, E>,
     ) {
-        if self.cursor.current().data().is_some() {
-            node.commit_whole(self.mode, context);
-        } else {
-            for cursor in self.cursor.children() {
-                let id = cursor.current().id();
-                self.cursor = cursor;
-                node.children.for_id(id, self, context);
+        let cursor = self.cursor;
+        // Entries that have been committed along with their ancestors are no longer dirty.
+        if cursor.current().data().is_some() && node.dirty {
+            // At least one entry is committed so that every unit of work makes progress.
+            if self.has_visited && self.deadline.did_timeout() {
+                // Every entry is committed as a whole, so this subtree can be committed later
+                // without leaving a widget half-updated.
+                let mut id_path = context.id_stack.id_path().to_vec();
+                self.deferred.push(id_path.clone());
+                for child in cursor.children() {
+                    defer_entries(child, &mut id_path, &mut self.deferred);
+                }
+                return;
             }
+            self.has_visited = true;
+            node.commit_whole(self.mode, context);
         }
+        // Descendants may have been updated apart from this node (e.g. memoized context
+        // consumers), so they are committed even if the node itself is.
+        for child in cursor.children() {
+            let id = child.current().id();
+            self.cursor = child;
+            node.children.for_id(id, self, context);
+        }
+        self.cursor = cursor;
+    }
+}
+
+fn defer_entries(
+    cursor: id_tree::Cursor<'_, ()>,
+    id_path: &mut IdPathBuf,
+    deferred: &mut Vec<IdPathBuf>,
+) {
+    id_path.push(cursor.current().id());
+    if cursor.current().data().is_some() {
+        deferred.push(id_path.clone());
+    }
+    for child in cursor.children() {
+        defer_entries(child, id_path, deferred);
     }
+    id_path.pop();
 }
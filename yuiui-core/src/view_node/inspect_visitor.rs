@@ -0,0 +1,53 @@
+use std::{any, mem};
+
+use crate::component_stack::ComponentStack;
+use crate::context::RenderContext;
+use crate::inspector::InspectedNode;
+use crate::view::View;
+
+use super::{Traversable, ViewNode, Visitor};
+
+pub struct InspectVisitor {
+    result: Vec<InspectedNode>,
+}
+
+impl InspectVisitor {
+    pub fn new() -> Self {
+        Self { result: Vec::new() }
+    }
+
+    pub fn into_result(self) -> Vec<InspectedNode> {
+        self.result
+    }
+}
+
+impl<'context, V, CS, S, M, E> Visitor<ViewNode<V, CS, S, M, E>, RenderContext<'context, S>>
+    for InspectVisitor
+where
+    V: View<S, M, E>,
+    CS: ComponentStack<S, M, E, View = V>,
+{
+    fn visit(
+        &mut self,
+        node: &mut ViewNode<V, CS, S, M, E>,
+        context: &mut RenderContext<'context, S>,
+    ) {
+        let siblings = mem::take(&mut self.result);
+        node.children.for_each(self, context);
+        let children = mem::replace(&mut self.result, siblings);
+        let mut components = Vec::new();
+        node.components.component_names(&mut components);
+        self.result.push(InspectedNode {
+            id: node.id,
+            id_path: context.id_stack.id_path().to_vec(),
+            view: any::type_name::<V>().to_owned(),
+            components: components.into_iter().map(ToOwned::to_owned).collect(),
+            dirty: node.dirty,
+            // The view state is kept after unmounting so that the node can be remounted.
+            mounted: context.subscriptions.is_mounted(node.id),
+            pending: node.pending_view.is_some(),
+            props: node.view.inspect(),
+            children,
+        });
+    }
+}
@@ -1,6 +1,6 @@
 use crate::component_stack::ComponentStack;
 use crate::context::RenderContext;
-use crate::id::{id_tree, IdPathBuf, Level};
+use crate::id::{id_tree, IdPathBuf, Level, NodePath};
 use crate::view::View;
 
 use super::{Traversable, ViewNode, Visitor};
@@ -8,6 +8,7 @@ use super::{Traversable, ViewNode, Visitor};
 pub struct UpdateSubtreeVisitor<'a> {
     cursor: id_tree::Cursor<'a, Level>,
     result: Vec<IdPathBuf>,
+    has_visited: bool,
 }
 
 impl<'a> UpdateSubtreeVisitor<'a> {
@@ -15,6 +16,7 @@ impl<'a> UpdateSubtreeVisitor<'a> {
         Self {
             cursor,
             result: Vec::new(),
+            has_visited: false,
         }
     }
 
@@ -34,7 +36,9 @@ where
         node: &mut ViewNode<V, CS, S, M, E>,
         context: &mut RenderContext<'context, S>,
     ) {
-        if let Some(&level) = self.cursor.current().data() {
+        let cursor = self.cursor;
+        if let Some(&level) = cursor.current().data() {
+            self.has_visited = true;
             let is_updated = if level > 0 {
                 CS::force_update(&mut node.into(), level, context)
             } else {
@@ -42,15 +46,57 @@ where
                 node.children.for_each(self, context);
                 true
             };
+            if let Some(profiler) = context.profiler {
+                profiler.record_visit(is_updated);
+            }
             if is_updated {
                 self.result.push(context.id_stack.id_path().to_vec());
             }
-        } else {
-            for cursor in self.cursor.children() {
-                let id = cursor.current().id();
-                self.cursor = cursor;
-                node.children.for_id(id, self, context);
+            // Consumers of the contexts provided in this subtree are updated in the same pass.
+            loop {
+                let consumers = context
+                    .providers
+                    .take_pending_updates_within(context.id_stack.id_path());
+                if consumers.is_empty() {
+                    break;
+                }
+                let mut visitor = UpdateSubtreeVisitor::new(consumers.root());
+                visitor.visit(node, context);
+                self.result.extend(visitor.into_result());
+            }
+        }
+        // Descendants may be skipped by the update of this node (e.g. memoized subscribers of
+        // other atoms), so they are updated on their own.
+        for child in cursor.children() {
+            // At least one entry is updated so that every unit of work makes progress.
+            if self.has_visited && context.did_timeout() {
+                // The remaining entries are updated later in their own unit of work.
+                let mut id_path = context.id_stack.id_path().to_vec();
+                defer_entries(child, &mut id_path, context.pending_updates);
+                continue;
             }
+            let id = child.current().id();
+            self.cursor = child;
+            node.children.for_id(id, self, context);
         }
+        self.cursor = cursor;
+    }
+}
+
+fn defer_entries(
+    cursor: id_tree::Cursor<'_, Level>,
+    id_path: &mut IdPathBuf,
+    pending_updates: &mut Vec<NodePath>,
+) {
+    id_path.push(cursor.current().id());
+    if let Some(&level) = cursor.current().data() {
+        pending_updates.push(NodePath {
+            id_path: id_path.clone(),
+            level,
+        });
+    }
+    for child in cursor.children() {
+        defer_entries(child, id_path, pending_updates);
     }
+    id_path.pop();
 }
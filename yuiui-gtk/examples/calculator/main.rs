@@ -2,7 +2,7 @@ mod calculator;
 
 use gtk::prelude::*;
 use gtk::{gdk, pango};
-use yuiui_core::{hlist, Atom, Effect, HigherOrderComponent, RenderContext, State, View};
+use yuiui_core::{hlist, Atom, Commands, Effect, HigherOrderComponent, RenderContext, State, View};
 use yuiui_gtk::views::{vbox, Button, Grid, GridChild, Label};
 use yuiui_gtk::{EntryPoint, GtkElement};
 
@@ -24,7 +24,11 @@ impl AppState {
 impl State for AppState {
     type Message = AppMessage;
 
-    fn update(&mut self, message: Self::Message) -> Effect {
+    fn update(
+        &mut self,
+        message: Self::Message,
+        _commands: &mut Commands<Self::Message>,
+    ) -> Effect {
         match message {
             AppMessage::PushDigit(digit) => self
                 .calculator
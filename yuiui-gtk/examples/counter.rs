@@ -1,5 +1,5 @@
 use gtk::prelude::*;
-use yuiui_core::{hlist, Atom, Effect, HigherOrderComponent, RenderContext, State, View};
+use yuiui_core::{hlist, Atom, Commands, Effect, HigherOrderComponent, RenderContext, State, View};
 use yuiui_gtk::views::{Button, Grid, GridChild, Label};
 use yuiui_gtk::{EntryPoint, GtkElement};
 
@@ -11,7 +11,11 @@ struct AppState {
 impl State for AppState {
     type Message = AppMessage;
 
-    fn update(&mut self, message: Self::Message) -> Effect {
+    fn update(
+        &mut self,
+        message: Self::Message,
+        _commands: &mut Commands<Self::Message>,
+    ) -> Effect {
         match message {
             AppMessage::Increment => self.count.update(|count| *count += 1),
             AppMessage::Decrement => self.count.update(|count| *count -= 1),
@@ -1,7 +1,8 @@
 use gtk::prelude::*;
 use std::rc::Rc;
 use yuiui_core::{
-    hlist, Atom, Effect, HigherOrderComponent, RenderContext, State, View, ViewElement,
+    hlist, Atom, Commands, Effect, HigherOrderComponent, Keyed, RenderContext, State, View,
+    ViewElement,
 };
 use yuiui_gtk::views::{hbox, vbox, Button, Entry, Label, ListBox, ListBoxRow, ScrolledWindow};
 use yuiui_gtk::{EntryPoint, GtkElement};
@@ -16,7 +17,11 @@ struct TodoState {
 impl State for TodoState {
     type Message = TodoMessage;
 
-    fn update(&mut self, message: Self::Message) -> Effect {
+    fn update(
+        &mut self,
+        message: Self::Message,
+        _commands: &mut Commands<Self::Message>,
+    ) -> Effect {
         match message {
             TodoMessage::AddTodo(text) => {
                 let todo = Todo {
@@ -108,8 +113,8 @@ fn todo_list(
     let todos = context.use_atom(|state| &state.todos);
     ListBox::new().hexpand(true).el(todos
         .iter()
-        .map(|todo| todo_item.memoize(TodoProps { todo: todo.clone() }))
-        .collect::<Vec<_>>())
+        .map(|todo| (todo.id, todo_item.memoize(TodoProps { todo: todo.clone() })))
+        .collect::<Keyed<_, _>>())
 }
 
 fn todo_item(
@@ -1,19 +1,25 @@
 use futures::stream::StreamExt as _;
 use gtk::glib;
 use std::sync::mpsc::Sender;
-use yuiui_core::{CancellationToken, Command, RawToken, RawTokenVTable};
+use yuiui_core::{CancellationToken, Command, Effect, RawToken, RawTokenVTable};
 
 #[derive(Debug)]
 pub struct CommandRuntime<M> {
     main_context: glib::MainContext,
     message_sender: Sender<M>,
+    effect_sender: Sender<Effect>,
 }
 
 impl<M: Send + 'static> CommandRuntime<M> {
-    pub(super) fn new(main_context: glib::MainContext, message_sender: Sender<M>) -> Self {
+    pub(super) fn new(
+        main_context: glib::MainContext,
+        message_sender: Sender<M>,
+        effect_sender: Sender<Effect>,
+    ) -> Self {
         Self {
             main_context,
             message_sender,
+            effect_sender,
         }
     }
 
@@ -33,28 +39,52 @@ impl<M: Send + 'static> CommandRuntime<M> {
 
 impl<M: Send + 'static> yuiui_core::CommandRuntime<M> for CommandRuntime<M> {
     fn spawn_command(&self, command: Command<M>, cancellation_token: Option<CancellationToken>) {
-        let message_sender = self.message_sender.clone();
+        self.spawn(self.message_sender.clone(), command, cancellation_token);
+    }
+
+    fn spawn_effect(
+        &self,
+        command: Command<Effect>,
+        cancellation_token: Option<CancellationToken>,
+    ) {
+        self.spawn(self.effect_sender.clone(), command, cancellation_token);
+    }
+}
+
+impl<M> CommandRuntime<M> {
+    fn spawn<T: Send + 'static>(
+        &self,
+        sender: Sender<T>,
+        command: Command<T>,
+        cancellation_token: Option<CancellationToken>,
+    ) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            kind = command.kind(),
+            cancellable = cancellation_token.is_some(),
+            "spawn command on main context"
+        );
         let main_context = self.main_context.clone();
         let source_id = match command {
             Command::Future(future) => self.main_context.spawn_local(async move {
-                let message = future.await;
-                message_sender.send(message).unwrap();
+                let value = future.await;
+                sender.send(value).unwrap();
                 main_context.wakeup();
             }),
             Command::Stream(mut stream) => self.main_context.spawn_local(async move {
-                while let Some(message) = stream.next().await {
-                    message_sender.send(message).unwrap();
+                while let Some(value) = stream.next().await {
+                    sender.send(value).unwrap();
                     main_context.wakeup();
                 }
             }),
             Command::Timeout(duration, callback) => glib::timeout_add_once(duration, move || {
-                let message = callback();
-                message_sender.send(message).unwrap();
+                let value = callback();
+                sender.send(value).unwrap();
                 main_context.wakeup();
             }),
             Command::Interval(period, mut callback) => glib::timeout_add(period, move || {
-                let message = callback();
-                message_sender.send(message).unwrap();
+                let value = callback();
+                sender.send(value).unwrap();
                 main_context.wakeup();
                 glib::Continue(true)
             }),
@@ -70,6 +100,8 @@ fn create_token(source_id: glib::SourceId) -> RawToken {
     static VTABLE: RawTokenVTable = RawTokenVTable::new(cancel, drop);
 
     unsafe fn cancel(data: *const ()) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("remove command source");
         Box::from_raw(data as *mut glib::SourceId).remove();
     }
 
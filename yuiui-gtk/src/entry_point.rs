@@ -1,14 +1,19 @@
 use gtk::glib;
 use gtk::prelude::*;
+use std::any::Any;
 use std::cell::RefCell;
+use std::fmt;
 use std::rc::Rc;
 use std::sync::mpsc;
 use std::time::{Duration, Instant};
-use yuiui_core::{Element, EventDestination, EventPayload, RenderFlow, RenderLoop, State, View};
+use yuiui_core::{
+    BoxedElement, Element, EventDestination, EventPayload, FrameDeadline, Id, Middleware, Priority,
+    Profiler, RenderFlow, RenderLoop, State, View,
+};
 
 use crate::command_runtime::CommandRuntime;
 
-const DEALINE_PERIOD: Duration = Duration::from_millis(50);
+const DEFAULT_REFRESH_INTERVAL: i64 = 16_667;
 
 #[derive(Debug, Clone)]
 pub struct EntryPoint {
@@ -20,21 +25,52 @@ impl EntryPoint {
         Self {
             inner: Rc::new(Inner {
                 window,
+                overlay: RefCell::new(None),
                 pending_events: RefCell::new(Vec::new()),
+                pending_roots: RefCell::new(Vec::new()),
             }),
         }
     }
 
-    pub fn run<S, M, E>(self, element: E, mut state: S)
+    pub fn builder<S, M>(window: gtk::ApplicationWindow) -> EntryPointBuilder<S, M> {
+        EntryPointBuilder {
+            window,
+            middlewares: Vec::new(),
+            profiler: None,
+        }
+    }
+
+    pub fn run<S, M, E>(self, element: E, state: S)
     where
         E: Element<S, M, Self> + 'static,
         <E::View as View<S, M, Self>>::State: AsRef<gtk::Widget>,
         S: State<Message = M> + 'static,
         M: Send + 'static,
+    {
+        self.run_with_options(element, state, Vec::new(), None)
+    }
+
+    fn run_with_options<S, M, E>(
+        self,
+        element: E,
+        mut state: S,
+        middlewares: Vec<Box<dyn Middleware<S, M>>>,
+        profiler: Option<Rc<Profiler>>,
+    ) where
+        E: Element<S, M, Self> + 'static,
+        <E::View as View<S, M, Self>>::State: AsRef<gtk::Widget>,
+        S: State<Message = M> + 'static,
+        M: Send + 'static,
     {
         let (message_tx, message_rx) = mpsc::channel();
-        let command_runtime = CommandRuntime::new(glib::MainContext::default(), message_tx);
-        let mut render_loop = RenderLoop::create(element, &mut state);
+        let (effect_tx, effect_rx) = mpsc::channel();
+        let command_runtime =
+            CommandRuntime::new(glib::MainContext::default(), message_tx, effect_tx);
+        let mut render_loop = RenderLoop::create_with_profiler(element, &state, profiler);
+
+        for middleware in middlewares {
+            render_loop.add_boxed_middleware(middleware);
+        }
 
         render_loop.run_forever(&mut state, &self, &command_runtime);
 
@@ -42,6 +78,8 @@ impl EntryPoint {
 
         self.attach_widget(widget);
 
+        let mut roots: Vec<(gtk::Window, Id, RootWidget)> = Vec::new();
+
         while gtk::Window::toplevels().n_items() > 0 {
             let main_context = command_runtime.main_context();
             let mut needs_render = false;
@@ -52,8 +90,37 @@ impl EntryPoint {
                     needs_render = true;
                 }
 
-                for (destination, payload) in self.inner.pending_events.borrow_mut().drain(..) {
-                    render_loop.push_event(destination, payload);
+                while let Ok(effect) = effect_rx.try_recv() {
+                    render_loop.push_effect(effect);
+                    needs_render = true;
+                }
+
+                for (destination, payload, priority) in
+                    self.inner.pending_events.borrow_mut().drain(..)
+                {
+                    render_loop.push_event_with_priority(destination, payload, priority);
+                    needs_render = true;
+                }
+
+                for request in self.inner.pending_roots.take() {
+                    match request {
+                        RootRequest::Mount(window, element, widget) => {
+                            let element = *element
+                                .downcast::<BoxedElement<S, M, Self, dyn Any>>()
+                                .unwrap_or_else(|_| {
+                                    panic!("The root element must share the state and message types of the main element")
+                                });
+                            let id = render_loop.mount_boxed_root(element, &state);
+                            roots.push((window, id, widget));
+                        }
+                        RootRequest::Unmount(window) => {
+                            if let Some(index) = roots.iter().position(|(w, _, _)| w == &window) {
+                                let (window, id, _) = roots.remove(index);
+                                window.set_child(None::<&gtk::Widget>);
+                                render_loop.unmount_root(id);
+                            }
+                        }
+                    }
                     needs_render = true;
                 }
 
@@ -63,7 +130,7 @@ impl EntryPoint {
             }
 
             if needs_render {
-                let deadline = Instant::now() + DEALINE_PERIOD;
+                let deadline = self.frame_deadline();
 
                 if matches!(
                     render_loop.run_until(&mut state, &self, &command_runtime, &deadline),
@@ -71,33 +138,189 @@ impl EntryPoint {
                 ) {
                     command_runtime.request_rerender();
                 }
+
+                for (window, id, widget) in &roots {
+                    if window.child().is_none() {
+                        if let Some(widget) = render_loop
+                            .root(*id)
+                            .and_then(|node| node.view_state())
+                            .and_then(widget)
+                        {
+                            window.set_child(Some(widget));
+                            window.present();
+                        }
+                    }
+                }
             }
         }
 
+        render_loop.flush_middlewares(&state);
+
         if let Some(application) = self.inner.window.application() {
             application.quit();
         }
     }
 
     pub fn dispatch_event<T: Send + 'static>(&self, destination: EventDestination, payload: T) {
+        self.dispatch_event_with_priority(destination, payload, Priority::UserInput);
+    }
+
+    pub fn dispatch_event_with_priority<T: Send + 'static>(
+        &self,
+        destination: EventDestination,
+        payload: T,
+        priority: Priority,
+    ) {
         self.inner
             .pending_events
             .borrow_mut()
-            .push((destination, Box::new(payload)));
+            .push((destination, Box::new(payload), priority));
+    }
+
+    pub fn mount_root<S, M, T>(&self, window: &impl IsA<gtk::Window>, element: T)
+    where
+        T: Element<S, M, Self> + 'static,
+        <T::View as View<S, M, Self>>::State: AsRef<gtk::Widget> + 'static,
+        S: 'static,
+        M: 'static,
+    {
+        let element: BoxedElement<S, M, Self, dyn Any> =
+            BoxedElement::new(element, |view_state| view_state);
+        self.inner
+            .pending_roots
+            .borrow_mut()
+            .push(RootRequest::Mount(
+                window.clone().upcast(),
+                Box::new(element),
+                |view_state| {
+                    view_state
+                        .downcast_ref::<<T::View as View<S, M, Self>>::State>()
+                        .map(AsRef::as_ref)
+                },
+            ));
+    }
+
+    pub fn unmount_root(&self, window: &impl IsA<gtk::Window>) {
+        self.inner
+            .pending_roots
+            .borrow_mut()
+            .push(RootRequest::Unmount(window.clone().upcast()));
     }
 
     pub fn window(&self) -> &gtk::ApplicationWindow {
         &self.inner.window
     }
 
+    pub fn overlay(&self) -> gtk::Overlay {
+        // The overlay wraps the main widget only once something is attached to it.
+        self.inner
+            .overlay
+            .borrow_mut()
+            .get_or_insert_with(|| {
+                let overlay = gtk::Overlay::new();
+                let child = self.inner.window.child();
+                self.inner.window.set_child(Some(&overlay));
+                overlay.set_child(child.as_ref());
+                overlay
+            })
+            .clone()
+    }
+
+    fn frame_deadline(&self) -> FrameDeadline {
+        let (frame_time, refresh_interval) = match self.inner.window.frame_clock() {
+            Some(frame_clock) => {
+                let frame_time = frame_clock.frame_time();
+                let (refresh_interval, _) = frame_clock.refresh_info(frame_time);
+                (frame_time, refresh_interval)
+            }
+            None => (glib::monotonic_time(), DEFAULT_REFRESH_INTERVAL),
+        };
+        let refresh_interval = if refresh_interval > 0 {
+            refresh_interval
+        } else {
+            DEFAULT_REFRESH_INTERVAL
+        };
+        // The frame time may be that of a past frame while the window is idle, so the deadline is
+        // the end of the frame that is in progress now.
+        let elapsed = (glib::monotonic_time() - frame_time).rem_euclid(refresh_interval);
+        FrameDeadline::new(
+            Instant::now() - Duration::from_micros(elapsed as u64),
+            Duration::from_micros(refresh_interval as u64),
+        )
+    }
+
     fn attach_widget(&self, widget: &gtk::Widget) {
-        self.inner.window.set_child(Some(widget));
+        match &*self.inner.overlay.borrow() {
+            Some(overlay) => overlay.set_child(Some(widget)),
+            None => self.inner.window.set_child(Some(widget)),
+        }
         self.inner.window.show();
     }
 }
 
+pub struct EntryPointBuilder<S, M> {
+    window: gtk::ApplicationWindow,
+    middlewares: Vec<Box<dyn Middleware<S, M>>>,
+    profiler: Option<Rc<Profiler>>,
+}
+
+impl<S, M> EntryPointBuilder<S, M> {
+    pub fn middleware(mut self, middleware: impl Middleware<S, M> + 'static) -> Self {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+
+    pub fn middlewares(
+        mut self,
+        middlewares: impl IntoIterator<Item = Box<dyn Middleware<S, M>>>,
+    ) -> Self {
+        self.middlewares.extend(middlewares);
+        self
+    }
+
+    pub fn profiler(mut self, profiler: Rc<Profiler>) -> Self {
+        self.profiler = Some(profiler);
+        self
+    }
+
+    pub fn run<E>(self, element: E, state: S)
+    where
+        E: Element<S, M, EntryPoint> + 'static,
+        <E::View as View<S, M, EntryPoint>>::State: AsRef<gtk::Widget>,
+        S: State<Message = M> + 'static,
+        M: Send + 'static,
+    {
+        EntryPoint::new(self.window).run_with_options(
+            element,
+            state,
+            self.middlewares,
+            self.profiler,
+        )
+    }
+}
+
+impl<S, M> fmt::Debug for EntryPointBuilder<S, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntryPointBuilder")
+            .field("window", &self.window)
+            .field("middlewares", &self.middlewares.len())
+            .field("profiler", &self.profiler)
+            .finish()
+    }
+}
+
 #[derive(Debug)]
 struct Inner {
     window: gtk::ApplicationWindow,
-    pending_events: RefCell<Vec<(EventDestination, EventPayload)>>,
+    overlay: RefCell<Option<gtk::Overlay>>,
+    pending_events: RefCell<Vec<(EventDestination, EventPayload, Priority)>>,
+    pending_roots: RefCell<Vec<RootRequest>>,
+}
+
+type RootWidget = fn(&dyn Any) -> Option<&gtk::Widget>;
+
+#[derive(Debug)]
+enum RootRequest {
+    Mount(gtk::Window, Box<dyn Any>, RootWidget),
+    Unmount(gtk::Window),
 }
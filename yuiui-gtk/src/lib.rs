@@ -2,10 +2,12 @@ pub mod views;
 
 mod command_runtime;
 mod entry_point;
+mod portal;
 
-pub use entry_point::EntryPoint;
+pub use entry_point::{EntryPoint, EntryPointBuilder};
+pub use portal::{OverlayTarget, WindowTarget};
 
-use yuiui_core::{ComponentStack, Element, ElementSeq, View};
+use yuiui_core::{AnyElement, ComponentStack, Element, ElementSeq, View};
 
 pub trait GtkElement<S, M, E = EntryPoint>:
     Element<
@@ -54,3 +56,26 @@ where
 
     type Children = T::Children;
 }
+
+pub type AnyGtkElement<S, M, E = EntryPoint> = AnyElement<S, M, E, gtk::Widget>;
+
+pub trait IntoAnyElement<S, M, E = EntryPoint> {
+    fn into_any(self) -> AnyGtkElement<S, M, E>;
+}
+
+impl<T, S, M, E> IntoAnyElement<S, M, E> for T
+where
+    T: Element<S, M, E> + 'static,
+    <T::View as View<S, M, E>>::State: AsRef<gtk::Widget>,
+    S: 'static,
+    M: 'static,
+    E: 'static,
+{
+    fn into_any(self) -> AnyGtkElement<S, M, E> {
+        AnyElement::with_projection(self, as_widget)
+    }
+}
+
+fn as_widget<T: AsRef<gtk::Widget>>(view_state: &T) -> &gtk::Widget {
+    view_state.as_ref()
+}
@@ -0,0 +1,119 @@
+use gtk::prelude::*;
+use yuiui_core::{CommitContext, ComponentStack, PortalTarget, View, ViewNode};
+
+use crate::entry_point::EntryPoint;
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct OverlayTarget;
+
+impl<V, CS, S, M> PortalTarget<ViewNode<V, CS, S, M, EntryPoint>, S, M, EntryPoint>
+    for OverlayTarget
+where
+    V: View<S, M, EntryPoint>,
+    V::State: AsRef<gtk::Widget>,
+    CS: ComponentStack<S, M, EntryPoint, View = V>,
+{
+    fn attach(
+        &mut self,
+        node: &mut ViewNode<V, CS, S, M, EntryPoint>,
+        context: &mut CommitContext<S, M, EntryPoint>,
+    ) {
+        let widget = node.view_state().unwrap().as_ref();
+        context.entry_point().overlay().add_overlay(widget);
+    }
+
+    fn detach(
+        &mut self,
+        node: &mut ViewNode<V, CS, S, M, EntryPoint>,
+        context: &mut CommitContext<S, M, EntryPoint>,
+    ) {
+        let widget = node.view_state().unwrap().as_ref();
+        context.entry_point().overlay().remove_overlay(widget);
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WindowTarget {
+    title: Option<String>,
+    modal: bool,
+    window: Option<gtk::Window>,
+}
+
+impl WindowTarget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn modal(mut self, modal: bool) -> Self {
+        self.modal = modal;
+        self
+    }
+
+    pub fn window(&self) -> Option<&gtk::Window> {
+        self.window.as_ref()
+    }
+}
+
+impl PartialEq for WindowTarget {
+    fn eq(&self, other: &Self) -> bool {
+        self.title == other.title && self.modal == other.modal
+    }
+}
+
+impl<V, CS, S, M> PortalTarget<ViewNode<V, CS, S, M, EntryPoint>, S, M, EntryPoint> for WindowTarget
+where
+    V: View<S, M, EntryPoint>,
+    V::State: AsRef<gtk::Widget>,
+    CS: ComponentStack<S, M, EntryPoint, View = V>,
+{
+    fn attach(
+        &mut self,
+        node: &mut ViewNode<V, CS, S, M, EntryPoint>,
+        context: &mut CommitContext<S, M, EntryPoint>,
+    ) {
+        let widget = node.view_state().unwrap().as_ref();
+        let window = gtk::Window::builder()
+            .transient_for(context.entry_point().window())
+            .modal(self.modal)
+            .build();
+        window.set_title(self.title.as_deref());
+        window.set_child(Some(widget));
+        window.present();
+        self.window = Some(window);
+    }
+
+    fn retarget(
+        &mut self,
+        target: Self,
+        node: &mut ViewNode<V, CS, S, M, EntryPoint>,
+        context: &mut CommitContext<S, M, EntryPoint>,
+    ) {
+        match &self.window {
+            Some(window) if self.modal == target.modal => {
+                window.set_title(target.title.as_deref());
+                self.title = target.title;
+            }
+            _ => {
+                self.detach(node, context);
+                *self = target;
+                self.attach(node, context);
+            }
+        }
+    }
+
+    fn detach(
+        &mut self,
+        _node: &mut ViewNode<V, CS, S, M, EntryPoint>,
+        _context: &mut CommitContext<S, M, EntryPoint>,
+    ) {
+        if let Some(window) = self.window.take() {
+            window.set_child(None::<&gtk::Widget>);
+            window.destroy();
+        }
+    }
+}
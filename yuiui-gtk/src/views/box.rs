@@ -117,14 +117,22 @@ impl<'a> ReconcileChildrenVisitor<'a> {
     }
 }
 
-impl<'a, V, CS, S, M, E, Context> Visitor<ViewNode<V, CS, S, M, E>, Context>
+impl<'a, 'context, V, CS, S, M, E>
+    Visitor<ViewNode<V, CS, S, M, E>, CommitContext<'context, S, M, E>>
     for ReconcileChildrenVisitor<'a>
 where
     V: View<S, M, E>,
     V::State: AsRef<gtk::Widget>,
     CS: ComponentStack<S, M, E, View = V>,
 {
-    fn visit(&mut self, node: &mut ViewNode<V, CS, S, M, E>, _context: &mut Context) {
+    fn visit(
+        &mut self,
+        node: &mut ViewNode<V, CS, S, M, E>,
+        context: &mut CommitContext<'context, S, M, E>,
+    ) {
+        if context.is_in_portal() {
+            return;
+        }
         let new_widget: &gtk::Widget = node.view_state().unwrap().as_ref();
         loop {
             match self.current_child.take() {
@@ -133,8 +141,11 @@ where
                     break;
                 }
                 Some(child) if new_widget.parent().is_some() => {
-                    self.current_child = child.next_sibling();
-                    self.container.remove(&child);
+                    let prev_sibling = child.prev_sibling();
+                    self.container
+                        .reorder_child_after(new_widget, prev_sibling.as_ref());
+                    self.current_child = Some(child);
+                    break;
                 }
                 Some(child) => {
                     let prev_sibling = child.prev_sibling();
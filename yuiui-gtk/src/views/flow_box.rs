@@ -193,13 +193,21 @@ impl<'a> ReconcileChildrenVisitor<'a> {
     }
 }
 
-impl<'a, V, CS, S, M, E, Context> Visitor<ViewNode<V, CS, S, M, E>, Context>
+impl<'a, 'context, V, CS, S, M, E>
+    Visitor<ViewNode<V, CS, S, M, E>, CommitContext<'context, S, M, E>>
     for ReconcileChildrenVisitor<'a>
 where
     V: View<S, M, E, State = gtk::FlowBoxChild>,
     CS: ComponentStack<S, M, E, View = V>,
 {
-    fn visit(&mut self, node: &mut ViewNode<V, CS, S, M, E>, _context: &mut Context) {
+    fn visit(
+        &mut self,
+        node: &mut ViewNode<V, CS, S, M, E>,
+        context: &mut CommitContext<'context, S, M, E>,
+    ) {
+        if context.is_in_portal() {
+            return;
+        }
         let new_child = node.view_state().unwrap();
         loop {
             match self.current_child.take() {
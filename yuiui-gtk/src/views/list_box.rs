@@ -1,5 +1,6 @@
 use gtk::prelude::*;
 use gtk::{gdk, glib};
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use yuiui_core::{
     CommitContext, ComponentStack, Element, ElementSeq, EventTarget, Lifecycle, Traversable, View,
@@ -178,61 +179,74 @@ impl<'event, Child> EventTarget<'event> for ListBoxRow<Child> {
 
 pub struct ReconcileChildrenVisitor<'a> {
     container: &'a gtk::ListBox,
-    current_child: Option<gtk::Widget>,
-    index: i32,
+    rows: Vec<gtk::ListBoxRow>,
 }
 
 impl<'a> ReconcileChildrenVisitor<'a> {
     fn new(container: &'a gtk::ListBox) -> Self {
         Self {
             container,
-            current_child: container.first_child(),
-            index: 0,
+            rows: Vec::new(),
         }
     }
 }
 
-impl<'a, V, CS, S, M, E, Context> Visitor<ViewNode<V, CS, S, M, E>, Context>
+impl<'a, 'context, V, CS, S, M, E>
+    Visitor<ViewNode<V, CS, S, M, E>, CommitContext<'context, S, M, E>>
     for ReconcileChildrenVisitor<'a>
 where
     V: View<S, M, E, State = gtk::ListBoxRow>,
     CS: ComponentStack<S, M, E, View = V>,
 {
-    fn visit(&mut self, node: &mut ViewNode<V, CS, S, M, E>, _context: &mut Context) {
-        let new_widget: &gtk::Widget = node.view_state().unwrap().as_ref();
-        loop {
-            match self.current_child.take() {
-                Some(child) if new_widget == &child => {
-                    self.current_child = child.next_sibling();
-                    self.index += 1;
-                    break;
-                }
-                Some(child) if new_widget.parent().is_some() => {
-                    self.current_child = child.next_sibling();
-                    self.container.remove(&child);
-                    self.index += 1;
-                }
-                Some(child) => {
-                    self.container.insert(new_widget, self.index);
-                    self.current_child = Some(child);
-                    self.index += 1;
-                    break;
-                }
-                None => {
-                    self.container.append(new_widget);
-                    self.index += 1;
-                    break;
-                }
-            }
+    fn visit(
+        &mut self,
+        node: &mut ViewNode<V, CS, S, M, E>,
+        context: &mut CommitContext<'context, S, M, E>,
+    ) {
+        if context.is_in_portal() {
+            return;
         }
+        self.rows.push(node.view_state().unwrap().clone());
     }
 }
 
 impl<'a> Drop for ReconcileChildrenVisitor<'a> {
     fn drop(&mut self) {
-        while let Some(current_child) = self.current_child.take() {
-            self.container.remove(&current_child);
-            self.current_child = current_child.next_sibling();
+        let positions = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| (row.clone(), i))
+            .collect::<HashMap<_, _>>();
+
+        let mut index = 0;
+        while let Some(row) = self.container.row_at_index(index) {
+            if positions.contains_key(&row) {
+                index += 1;
+            } else {
+                self.container.remove(&row);
+            }
+        }
+
+        for (i, row) in self.rows.iter().enumerate() {
+            if row.parent().is_none() {
+                self.container.insert(row, i as i32);
+            }
+        }
+
+        // Rows are reordered by sorting rather than removing and inserting them, so that moved
+        // rows keep their focus and selection.
+        let is_sorted = self
+            .rows
+            .iter()
+            .enumerate()
+            .all(|(i, row)| row.index() == i as i32);
+        if !is_sorted {
+            self.container.set_sort_func(move |first, second| {
+                positions[first].cmp(&positions[second]).into()
+            });
+            self.container.invalidate_sort();
+            self.container.unset_sort_func();
         }
     }
 }
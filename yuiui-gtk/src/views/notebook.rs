@@ -153,8 +153,8 @@ impl<'a> ReconcileChildrenVisitor<'a> {
     }
 }
 
-impl<'a, Label, Content, CS, S, M, E, Context>
-    Visitor<ViewNode<NotebookChild<Label, Content>, CS, S, M, E>, Context>
+impl<'a, 'context, Label, Content, CS, S, M, E>
+    Visitor<ViewNode<NotebookChild<Label, Content>, CS, S, M, E>, CommitContext<'context, S, M, E>>
     for ReconcileChildrenVisitor<'a>
 where
     Label: Element<S, M, E>,
@@ -168,8 +168,11 @@ where
     fn visit(
         &mut self,
         node: &mut ViewNode<NotebookChild<Label, Content>, CS, S, M, E>,
-        _context: &mut Context,
+        context: &mut CommitContext<'context, S, M, E>,
     ) {
+        if context.is_in_portal() {
+            return;
+        }
         let new_label: &gtk::Widget = node.children().0.view_state().unwrap().as_ref();
         let new_child: &gtk::Widget = node.children().1.view_state().unwrap().as_ref();
 
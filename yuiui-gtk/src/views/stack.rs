@@ -245,7 +245,8 @@ impl<'a> ReconcileChildrenVisitor<'a> {
     }
 }
 
-impl<'a, Child, CS, S, M, E, Context> Visitor<ViewNode<StackPage<Child>, CS, S, M, E>, Context>
+impl<'a, 'context, Child, CS, S, M, E>
+    Visitor<ViewNode<StackPage<Child>, CS, S, M, E>, CommitContext<'context, S, M, E>>
     for ReconcileChildrenVisitor<'a>
 where
     Child: Element<S, M, E>,
@@ -256,8 +257,11 @@ where
     fn visit(
         &mut self,
         node: &mut ViewNode<StackPage<Child>, CS, S, M, E>,
-        _context: &mut Context,
+        context: &mut CommitContext<'context, S, M, E>,
     ) {
+        if context.is_in_portal() {
+            return;
+        }
         let new_child: &gtk::Widget = node.children().view_state().unwrap().as_ref();
         loop {
             match self.current_child.take() {